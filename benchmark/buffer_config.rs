@@ -48,7 +48,7 @@ pub fn bench_buffer_count(messages_per_config: usize) -> Vec<BufferConfigResult>
         let start = Instant::now();
         let counter = Arc::new(AtomicU64::new(0));
 
-        let ctx = DltContext::new("BCFG", "TST", "Buffer Config Bench", "Test");
+        let ctx = DltContext::new("BCFG", "TST", "Buffer Config Bench", "Test").expect("Failed to register context");
 
         for i in 0..messages_per_config {
             let _ = ctx.log(DltLogLevel::Info, i as i32, "BufferBench");
@@ -100,7 +100,7 @@ pub fn bench_buffer_sizes(messages_per_config: usize) -> Vec<BufferConfigResult>
         let start = Instant::now();
         let counter = Arc::new(AtomicU64::new(0));
 
-        let ctx = DltContext::new("BSIZ", "TST", "Buffer Size Bench", "Test");
+        let ctx = DltContext::new("BSIZ", "TST", "Buffer Size Bench", "Test").expect("Failed to register context");
 
         for i in 0..messages_per_config {
             let _ = ctx.log(DltLogLevel::Info, i as i32, "SizeBench");
@@ -143,7 +143,7 @@ pub fn bench_batch_sizes(messages_per_config: usize) -> Vec<BufferConfigResult>
         let start = Instant::now();
         let counter = Arc::new(AtomicU64::new(0));
 
-        let ctx = DltContext::new("BTCH", "TST", "Batch Size Bench", "Test");
+        let ctx = DltContext::new("BTCH", "TST", "Batch Size Bench", "Test").expect("Failed to register context");
 
         for i in 0..messages_per_config {
             let _ = ctx.log(DltLogLevel::Info, i as i32, "BatchBench");
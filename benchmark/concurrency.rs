@@ -22,7 +22,7 @@ pub fn bench_single_thread(num_messages: usize) -> ConcurrencyResult {
     println!("\n=== Benchmarking Single Thread ===");
 
     let start = Instant::now();
-    let ctx = DltContext::new("SNGL", "TST", "Single Thread Bench", "Test");
+    let ctx = DltContext::new("SNGL", "TST", "Single Thread Bench", "Test").expect("Failed to register context");
 
     for i in 0..num_messages {
         let _ = ctx.log(DltLogLevel::Info, i as i32, "SingleThread");
@@ -57,7 +57,7 @@ pub fn bench_multi_thread_mpsc(num_threads: usize, messages_per_thread: usize) -
         let counter_clone = Arc::clone(&counter);
         
         handles.push(thread::spawn(move || {
-            let ctx = DltContext::new("MPSC", "TST", "MPSC Bench", "Test");
+            let ctx = DltContext::new("MPSC", "TST", "MPSC Bench", "Test").expect("Failed to register context");
             
             for i in 0..messages_per_thread {
                 let _ = ctx.log(DltLogLevel::Info, i as i32, &format!("Thread{}", thread_id));
@@ -115,10 +115,10 @@ pub fn bench_burst_vs_sustained() -> Vec<ConcurrencyResult> {
     // Burst: 10K messages as fast as possible
     println!("\nBurst load: 10K messages (no delay)...");
     let start = Instant::now();
-    let ctx = DltContext::new("BRST", "TST", "Burst Bench", "Test");
+    let ctx = DltContext::new("BRST", "TST", "Burst Bench", "Test").expect("Failed to register context");
 
     for i in 0..10000 {
-        let _ = ctx.log(DltLogLevel::Info, i as i32, "Burst");
+        let _ = ctx.log(DltLogLevel::Info, i, "Burst");
     }
 
     let duration = start.elapsed();
@@ -138,10 +138,10 @@ pub fn bench_burst_vs_sustained() -> Vec<ConcurrencyResult> {
     // Sustained: 10K messages with 1ms delay between each
     println!("\nSustained load: 10K messages (1ms delay)...");
     let start = Instant::now();
-    let ctx = DltContext::new("SUST", "TST", "Sustained Bench", "Test");
+    let ctx = DltContext::new("SUST", "TST", "Sustained Bench", "Test").expect("Failed to register context");
 
     for i in 0..10000 {
-        let _ = ctx.log(DltLogLevel::Info, i as i32, "Sustained");
+        let _ = ctx.log(DltLogLevel::Info, i, "Sustained");
         thread::sleep(Duration::from_micros(1000));
     }
 
@@ -32,7 +32,7 @@ pub fn bench_drop_mode(num_messages: usize, num_threads: usize) -> OverflowModeR
         let stop_clone = Arc::clone(&stop);
 
         handles.push(thread::spawn(move || {
-            let ctx = DltContext::new("DRPB", "TST", "Drop Bench", "Test");
+            let ctx = DltContext::new("DRPB", "TST", "Drop Bench", "Test").expect("Failed to register context");
             let mut local_count = 0;
 
             while local_count < num_messages / num_threads && !stop_clone.load(Ordering::Relaxed) {
@@ -81,7 +81,7 @@ pub fn bench_overwrite_mode(num_messages: usize, num_threads: usize) -> Overflow
         let counter_clone = Arc::clone(&counter);
 
         handles.push(thread::spawn(move || {
-            let ctx = DltContext::new("OWRB", "TST", "Overwrite Bench", "Test");
+            let ctx = DltContext::new("OWRB", "TST", "Overwrite Bench", "Test").expect("Failed to register context");
             let mut local_count = 0;
 
             while local_count < num_messages / num_threads {
@@ -128,7 +128,7 @@ pub fn bench_timeout_mode(num_messages: usize, num_threads: usize) -> OverflowMo
         let counter_clone = Arc::clone(&counter);
 
         handles.push(thread::spawn(move || {
-            let ctx = DltContext::new("TIMB", "TST", "Timeout Bench", "Test");
+            let ctx = DltContext::new("TIMB", "TST", "Timeout Bench", "Test").expect("Failed to register context");
             let mut local_count = 0;
 
             while local_count < num_messages / num_threads {
@@ -35,7 +35,7 @@ pub fn bench_suspended_threads(num_threads: usize, num_suspended: usize, message
         let is_suspended = thread_id < num_suspended;
 
         handles.push(thread::spawn(move || {
-            let ctx = DltContext::new("SUSP", "TST", "Suspend Bench", "Test");
+            let ctx = DltContext::new("SUSP", "TST", "Suspend Bench", "Test").expect("Failed to register context");
 
             for i in 0..messages_per_thread {
                 // Simulated suspension: threads sleep for extended period
@@ -118,7 +118,7 @@ pub fn bench_cascading_suspensions(num_threads: usize, messages_per_thread: usiz
         let counter_clone = Arc::clone(&counter);
 
         handles.push(thread::spawn(move || {
-            let ctx = DltContext::new("CASC", "TST", "Cascade Bench", "Test");
+            let ctx = DltContext::new("CASC", "TST", "Cascade Bench", "Test").expect("Failed to register context");
 
             for i in 0..messages_per_thread {
                 // Staggered suspensions: each thread suspends at different point
@@ -171,7 +171,7 @@ pub fn bench_immediate_suspension() -> SuspendThreadResult {
 
     let counter_main = Arc::clone(&counter);
     let suspended_handle = thread::spawn(move || {
-        let ctx = DltContext::new("IMMD", "TST", "Immediate Suspend", "Test");
+        let ctx = DltContext::new("IMMD", "TST", "Immediate Suspend", "Test").expect("Failed to register context");
 
         // Log once, then suspend immediately
         let _ = ctx.log(DltLogLevel::Info, 0, "BeforeSuspend");
@@ -194,7 +194,7 @@ pub fn bench_immediate_suspension() -> SuspendThreadResult {
         let counter_clone = Arc::clone(&counter);
 
         other_handles.push(thread::spawn(move || {
-            let ctx = DltContext::new("OTHR", "TST", "Other Thread", "Test");
+            let ctx = DltContext::new("OTHR", "TST", "Other Thread", "Test").expect("Failed to register context");
 
             for i in 0..2000 {
                 let _ = ctx.log(DltLogLevel::Info, i, &format!("Thread{}", thread_id));
@@ -0,0 +1,129 @@
+// Broadcast buffer: every subscriber gets every item through its own lockless queue
+// A full queue means that subscriber is too slow to keep up; the policy decides
+// whether its oldest item is dropped or it is disconnected. Other subscribers are
+// never held back. The subscriber list is behind a mutex, taken by publish and
+// subscribe only.
+use crossbeam::queue::ArrayQueue;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SlowConsumerPolicy {
+    #[default]
+    DropOldest, // Make room in the full queue; the subscriber misses the oldest item
+    Disconnect, // Mark the subscriber disconnected and stop delivering to it
+}
+
+struct Subscription<T> {
+    queue: ArrayQueue<T>,
+    disconnected: AtomicBool,
+    dropped: AtomicU64,
+}
+
+pub struct BroadcastBuffer<T> {
+    subscriptions: Arc<Mutex<Vec<Arc<Subscription<T>>>>>,
+    capacity: usize,
+    policy: SlowConsumerPolicy,
+}
+
+impl<T: Clone> BroadcastBuffer<T> {
+    // `capacity` items per subscriber
+    pub fn new(capacity: usize, policy: SlowConsumerPolicy) -> Self {
+        BroadcastBuffer {
+            subscriptions: Arc::new(Mutex::new(Vec::new())),
+            capacity,
+            policy,
+        }
+    }
+
+    // Receives the items published from now on
+    pub fn subscribe(&self) -> BroadcastReceiver<T> {
+        let subscription = Arc::new(Subscription {
+            queue: ArrayQueue::new(self.capacity),
+            disconnected: AtomicBool::new(false),
+            dropped: AtomicU64::new(0),
+        });
+        self.subscriptions.lock().unwrap().push(Arc::clone(&subscription));
+        BroadcastReceiver { subscription }
+    }
+
+    // Queue the item for every subscriber; returns how many got it
+    pub fn publish(&self, item: T) -> usize {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        subscriptions.retain(|s| !s.disconnected.load(Ordering::Relaxed));
+        let mut delivered = 0;
+        for subscription in subscriptions.iter() {
+            match self.policy {
+                SlowConsumerPolicy::DropOldest => {
+                    if subscription.queue.force_push(item.clone()).is_some() {
+                        subscription.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    delivered += 1;
+                }
+                SlowConsumerPolicy::Disconnect => {
+                    if subscription.queue.push(item.clone()).is_ok() {
+                        delivered += 1;
+                    } else {
+                        subscription.dropped.fetch_add(1, Ordering::Relaxed);
+                        subscription.disconnected.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+        delivered
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        let subscriptions = self.subscriptions.lock().unwrap();
+        subscriptions.iter().filter(|s| !s.disconnected.load(Ordering::Relaxed)).count()
+    }
+
+    pub fn policy(&self) -> SlowConsumerPolicy {
+        self.policy
+    }
+}
+
+impl<T> Clone for BroadcastBuffer<T> {
+    fn clone(&self) -> Self {
+        BroadcastBuffer {
+            subscriptions: Arc::clone(&self.subscriptions),
+            capacity: self.capacity,
+            policy: self.policy,
+        }
+    }
+}
+
+// One subscriber's queue; dropping it unsubscribes
+pub struct BroadcastReceiver<T> {
+    subscription: Arc<Subscription<T>>,
+}
+
+impl<T> BroadcastReceiver<T> {
+    pub fn pop(&self) -> Option<T> {
+        self.subscription.queue.pop()
+    }
+
+    // Set by SlowConsumerPolicy::Disconnect; items still queued can be popped
+    pub fn is_disconnected(&self) -> bool {
+        self.subscription.disconnected.load(Ordering::Relaxed)
+    }
+
+    // Items this subscriber missed because its queue was full
+    pub fn dropped(&self) -> u64 {
+        self.subscription.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.subscription.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscription.queue.is_empty()
+    }
+}
+
+impl<T> Drop for BroadcastReceiver<T> {
+    fn drop(&mut self) {
+        self.subscription.disconnected.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,61 @@
+// History ring: the most recent items, bounded by their total size in bytes
+// Pushing evicts the oldest items until the new one fits; an item larger than the
+// whole ring is not kept. Not synchronized; share it behind a lock.
+use std::collections::VecDeque;
+
+pub struct HistoryRing<T> {
+    items: VecDeque<T>,
+    bytes: usize,
+    max_bytes: usize,
+    evicted: u64,
+}
+
+impl<T: AsRef<[u8]>> HistoryRing<T> {
+    // A ring of 0 bytes keeps nothing
+    pub fn new(max_bytes: usize) -> Self {
+        HistoryRing { items: VecDeque::new(), bytes: 0, max_bytes, evicted: 0 }
+    }
+
+    pub fn push(&mut self, item: T) {
+        let size = item.as_ref().len();
+        if size > self.max_bytes {
+            self.evicted += 1;
+            return;
+        }
+        while self.bytes + size > self.max_bytes {
+            let Some(oldest) = self.items.pop_front() else {
+                break;
+            };
+            self.bytes -= oldest.as_ref().len();
+            self.evicted += 1;
+        }
+        self.bytes += size;
+        self.items.push_back(item);
+    }
+
+    // Oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    // Items pushed out (or never kept) so far
+    pub fn evicted(&self) -> u64 {
+        self.evicted
+    }
+}
@@ -1,4 +1,8 @@
 // Buffer module: Lockless data structures
+pub mod broadcast;
+pub mod history;
 pub mod lockless;
 
+pub use broadcast::*;
+pub use history::*;
 pub use lockless::*;
@@ -0,0 +1,183 @@
+// catalog: Non-verbose message catalog (FIBEX-like description stored as JSON)
+//
+// {
+//   "messages": [
+//     { "id": 1000, "apid": "ENG", "ctid": "SPD",
+//       "pdus": [ { "text": "Engine speed" },
+//                 { "type": "uint16", "name": "rpm", "unit": "1/min" } ] }
+//   ]
+// }
+use dlt_core::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+// Packed signal types, mirroring FIBEX base data types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignalType {
+    Bool,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Uint8,
+    Uint16,
+    Uint32,
+    Uint64,
+    Hex8,
+    Hex16,
+    Hex32,
+    Hex64,
+    Float32,
+    Float64,
+    String,
+    Utf8,
+    Raw,
+}
+
+impl SignalType {
+    // Type info field used to decode the packed value
+    pub fn type_info(self) -> u32 {
+        match self {
+            SignalType::Bool => DLT_TYPE_INFO_BOOL | DLT_TYLE_8BIT,
+            SignalType::Int8 => DLT_TYPE_INFO_SINT | DLT_TYLE_8BIT,
+            SignalType::Int16 => DLT_TYPE_INFO_SINT | DLT_TYLE_16BIT,
+            SignalType::Int32 => DLT_TYPE_INFO_SINT | DLT_TYLE_32BIT,
+            SignalType::Int64 => DLT_TYPE_INFO_SINT | DLT_TYLE_64BIT,
+            SignalType::Uint8 => DLT_TYPE_INFO_UINT | DLT_TYLE_8BIT,
+            SignalType::Uint16 => DLT_TYPE_INFO_UINT | DLT_TYLE_16BIT,
+            SignalType::Uint32 => DLT_TYPE_INFO_UINT | DLT_TYLE_32BIT,
+            SignalType::Uint64 => DLT_TYPE_INFO_UINT | DLT_TYLE_64BIT,
+            SignalType::Hex8 => DLT_TYPE_INFO_UINT | DLT_TYLE_8BIT | DLT_SCOD_HEX,
+            SignalType::Hex16 => DLT_TYPE_INFO_UINT | DLT_TYLE_16BIT | DLT_SCOD_HEX,
+            SignalType::Hex32 => DLT_TYPE_INFO_UINT | DLT_TYLE_32BIT | DLT_SCOD_HEX,
+            SignalType::Hex64 => DLT_TYPE_INFO_UINT | DLT_TYLE_64BIT | DLT_SCOD_HEX,
+            SignalType::Float32 => DLT_TYPE_INFO_FLOA | DLT_TYLE_32BIT,
+            SignalType::Float64 => DLT_TYPE_INFO_FLOA | DLT_TYLE_64BIT,
+            SignalType::String => DLT_TYPE_INFO_STRG | DLT_SCOD_ASCII,
+            SignalType::Utf8 => DLT_TYPE_INFO_STRG | DLT_SCOD_UTF8,
+            SignalType::Raw => DLT_TYPE_INFO_RAWD,
+        }
+    }
+}
+
+// One PDU of a catalog frame: either static text or a packed signal
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CatalogPdu {
+    Text {
+        text: String,
+    },
+    Signal {
+        #[serde(rename = "type")]
+        kind: SignalType,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        unit: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogMessage {
+    pub id: u32,
+    #[serde(default, deserialize_with = "deserialize_id")]
+    pub apid: Option<AppId>,
+    #[serde(default, deserialize_with = "deserialize_id")]
+    pub ctid: Option<ContextId>,
+    #[serde(default)]
+    pub pdus: Vec<CatalogPdu>,
+}
+
+impl CatalogMessage {
+    fn signal_types(&self) -> Vec<u32> {
+        self.pdus.iter()
+            .filter_map(|pdu| match pdu {
+                CatalogPdu::Signal { kind, .. } => Some(kind.type_info()),
+                CatalogPdu::Text { .. } => None,
+            })
+            .collect()
+    }
+
+    // Render static text and decoded signal values, separated by spaces
+    pub fn render(&self, data: &[u8], big_endian: bool) -> Option<String> {
+        let mut args = decode_packed_arguments(data, &self.signal_types(), big_endian).ok()?.into_iter();
+        let mut parts = Vec::with_capacity(self.pdus.len());
+        for pdu in &self.pdus {
+            match pdu {
+                CatalogPdu::Text { text } => parts.push(text.clone()),
+                CatalogPdu::Signal { unit, .. } => {
+                    let value = args.next()?.to_string();
+                    match unit {
+                        Some(unit) => parts.push(format!("{} {}", value, unit)),
+                        None => parts.push(value),
+                    }
+                }
+            }
+        }
+        Some(parts.join(" "))
+    }
+}
+
+// "apid"/"ctid" strings, rejected unless they are valid DLT IDs
+fn deserialize_id<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: std::str::FromStr<Err = DltIdError>,
+{
+    let id = String::deserialize(deserializer)?;
+    id.parse()
+        .map(Some)
+        .map_err(|e| serde::de::Error::custom(format!("invalid ID \"{}\": {}", id, e)))
+}
+
+#[derive(Deserialize)]
+struct CatalogFile {
+    messages: Vec<CatalogMessage>,
+}
+
+// Message ID -> frame description lookup
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalog {
+    messages: HashMap<u32, CatalogMessage>,
+}
+
+impl MessageCatalog {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let file: CatalogFile = serde_json::from_str(json)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut messages = HashMap::with_capacity(file.messages.len());
+        for message in file.messages {
+            let id = message.id;
+            if messages.insert(id, message).is_some() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Duplicate message ID {}", id)));
+            }
+        }
+        Ok(MessageCatalog { messages })
+    }
+
+    pub fn get(&self, message_id: u32) -> Option<&CatalogMessage> {
+        self.messages.get(&message_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    // Render a non-verbose message, if its ID is known
+    pub fn render(&self, msg: &DltMessage) -> Option<String> {
+        let entry = self.get(msg.message_id()?)?;
+        entry.render(msg.non_verbose_data()?, msg.is_big_endian())
+    }
+}
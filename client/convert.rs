@@ -0,0 +1,213 @@
+// convert: Offline output formats and message filters (used by dlt-convert)
+use crate::{message_header_text, message_ids, message_payload_text, storage_time_text, MessageCatalog};
+use dlt_core::{AppId, ContextId, DltMessage, LogLevel, DLT_TIMESTAMP_RESOLUTION};
+use dlt_storage::storage_time_us;
+use std::fmt::Write;
+use std::str::FromStr;
+
+// Column names of DltOutputFormat::Csv
+pub const DLT_CSV_HEADER: &str = "index,time,timestamp,mcnt,ecu,apid,ctid,type,subtype,mode,noar,payload";
+
+const MIXED_BYTES_PER_LINE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DltOutputFormat {
+    #[default]
+    Text,      // parse_message_text
+    Hex,       // Header, then the raw payload as hex bytes
+    Ascii,     // Header, then the raw payload with non-printable bytes as '.'
+    Mixed,     // Header, then a hex and ASCII dump of the payload, 16 bytes per line
+    Csv,       // One row per message, see DLT_CSV_HEADER
+    JsonLines, // One JSON object per line
+}
+
+impl DltOutputFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DltOutputFormat::Text => "text",
+            DltOutputFormat::Hex => "hex",
+            DltOutputFormat::Ascii => "ascii",
+            DltOutputFormat::Mixed => "mixed",
+            DltOutputFormat::Csv => "csv",
+            DltOutputFormat::JsonLines => "jsonl",
+        }
+    }
+}
+
+impl FromStr for DltOutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(DltOutputFormat::Text),
+            "hex" => Ok(DltOutputFormat::Hex),
+            "ascii" => Ok(DltOutputFormat::Ascii),
+            "mixed" => Ok(DltOutputFormat::Mixed),
+            "csv" => Ok(DltOutputFormat::Csv),
+            "jsonl" | "json" => Ok(DltOutputFormat::JsonLines),
+            _ => Err(format!("unknown format '{}' (text, hex, ascii, mixed, csv, jsonl)", s)),
+        }
+    }
+}
+
+// Message `index` (its position in the input) in an output format, without a
+// trailing newline
+pub fn format_message(index: u64, msg: &DltMessage, format: DltOutputFormat, catalog: Option<&MessageCatalog>) -> String {
+    let header = || {
+        message_header_text(msg, catalog)
+            .unwrap_or_else(|| format!("{} <no extended header>", storage_time_text(msg)))
+    };
+    match format {
+        DltOutputFormat::Text => crate::parse_message_text_with_catalog(msg, catalog),
+        DltOutputFormat::Hex => format!("{} [{}]", header(), hex_bytes(&msg.payload)),
+        DltOutputFormat::Ascii => format!("{} [{}]", header(), ascii_bytes(&msg.payload)),
+        DltOutputFormat::Mixed => {
+            let mut out = header();
+            for (i, line) in msg.payload.chunks(MIXED_BYTES_PER_LINE).enumerate() {
+                let _ = write!(out, "\n{:04x}: {:<47}  {}", i * MIXED_BYTES_PER_LINE, hex_bytes(line), ascii_bytes(line));
+            }
+            out
+        }
+        DltOutputFormat::Csv => {
+            let fields = MessageFields::new(msg, catalog);
+            let columns = [
+                index.to_string(),
+                fields.time,
+                fields.timestamp,
+                fields.mcnt.to_string(),
+                fields.ecu,
+                fields.apid,
+                fields.ctid,
+                fields.message_type.to_string(),
+                fields.subtype.to_string(),
+                if fields.verbose { "V" } else { "N" }.to_string(),
+                fields.noar.to_string(),
+                fields.payload,
+            ];
+            columns.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(",")
+        }
+        DltOutputFormat::JsonLines => {
+            let fields = MessageFields::new(msg, catalog);
+            serde_json::json!({
+                "index": index,
+                "time": fields.time,
+                "timestamp": fields.timestamp,
+                "mcnt": fields.mcnt,
+                "ecu": fields.ecu,
+                "apid": fields.apid,
+                "ctid": fields.ctid,
+                "type": fields.message_type,
+                "subtype": fields.subtype,
+                "verbose": fields.verbose,
+                "noar": fields.noar,
+                "payload": fields.payload,
+            })
+            .to_string()
+        }
+    }
+}
+
+// Columns shared by the CSV and JSON formats
+struct MessageFields {
+    time: String,
+    timestamp: String, // Seconds with 0.1 ms resolution
+    mcnt: u8,
+    ecu: String,
+    apid: String,
+    ctid: String,
+    message_type: &'static str,
+    subtype: &'static str,
+    verbose: bool,
+    noar: u8,
+    payload: String,
+}
+
+impl MessageFields {
+    fn new(msg: &DltMessage, catalog: Option<&MessageCatalog>) -> Self {
+        let (apid, ctid, noar) = message_ids(msg, catalog).unwrap_or_default();
+        let info = msg.message_info();
+        let tmsp = msg.standard_header.timestamp.unwrap_or(0);
+        MessageFields {
+            time: storage_time_text(msg),
+            timestamp: format!("{}.{:04}", tmsp / DLT_TIMESTAMP_RESOLUTION, tmsp % DLT_TIMESTAMP_RESOLUTION),
+            mcnt: msg.standard_header.mcnt,
+            ecu: msg.storage_header.ecu.as_str(),
+            apid,
+            ctid,
+            message_type: info.map_or("", |info| info.type_str()),
+            subtype: info.map_or("", |info| info.subtype_str()),
+            verbose: msg.is_verbose(),
+            noar,
+            payload: message_payload_text(msg, catalog),
+        }
+    }
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
+
+fn ascii_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect()
+}
+
+// Quoted when it contains a separator, quote or line break (RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Message selection; empty ID lists and unset bounds match everything
+#[derive(Debug, Clone, Default)]
+pub struct DltMessageFilter {
+    pub apids: Vec<AppId>,
+    pub ctids: Vec<ContextId>,
+    pub max_level: Option<LogLevel>,  // Log messages up to this level; other types are dropped
+    pub start_time_us: Option<u64>,   // Storage time window, start inclusive
+    pub end_time_us: Option<u64>,     // End exclusive
+}
+
+impl DltMessageFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn matches(&self, msg: &DltMessage) -> bool {
+        let ext = msg.extended_header.as_ref();
+        if !self.apids.is_empty() && !ext.is_some_and(|ext| self.apids.contains(&ext.apid)) {
+            return false;
+        }
+        if !self.ctids.is_empty() && !ext.is_some_and(|ext| self.ctids.contains(&ext.ctid)) {
+            return false;
+        }
+        if let Some(max_level) = self.max_level {
+            // Only log messages have a level
+            match msg.message_info().and_then(|info| info.log_level()) {
+                Some(level) if level as u8 <= max_level as u8 => {}
+                _ => return false,
+            }
+        }
+        let time = storage_time_us(msg.storage_header.seconds, msg.storage_header.microseconds);
+        self.start_time_us.is_none_or(|start| time >= start) && self.end_time_us.is_none_or(|end| time < end)
+    }
+}
+
+// Time given as Unix seconds ("1700000000.25") or local date and time
+// ("2024-01-31 12:00:00", optionally with fractional seconds or a 'T' separator),
+// in microseconds since the Unix epoch
+pub fn parse_time(s: &str) -> Option<u64> {
+    if let Ok(secs) = s.parse::<f64>() {
+        return (secs >= 0.0).then(|| (secs * 1_000_000.0).round() as u64);
+    }
+    let formats = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y/%m/%d %H:%M:%S%.f", "%Y-%m-%d"];
+    let naive = formats.iter().find_map(|format| {
+        chrono::NaiveDateTime::parse_from_str(s, format)
+            .or_else(|_| chrono::NaiveDate::parse_from_str(s, format).map(|d| d.and_time(chrono::NaiveTime::MIN)))
+            .ok()
+    })?;
+    let local = naive.and_local_timezone(chrono::Local).earliest()?;
+    u64::try_from(local.timestamp_micros()).ok()
+}
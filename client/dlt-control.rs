@@ -1,34 +1,192 @@
-// Example: dlt-control tool skeleton
-// This demonstrates how to build control tools using the dlt-client library
-
+// dlt-control: send control requests to the daemon and print the response
+// Rust equivalent of dlt-daemon/src/console/dlt-control.c
 use dlt_client::DltClient;
+use dlt_core::*;
+use std::env;
+use std::time::Duration;
+
+fn usage() {
+    println!("Usage: dlt-control [options] hostname/serial_device_name");
+    println!("Send control message to DLT daemon.");
+    println!("Options:");
+    println!("  -a id         Control message application id");
+    println!("  -c id         Control message context id");
+    println!("  -l level      Set log level (-1..6)");
+    println!("  -d level      Set default log level (0..6)");
+    println!("  -s id         Control message injection service id");
+    println!("  -m message    Control message injection in ASCII");
+    println!("  -j            Get log info");
+    println!("  -V            Get software version");
+    println!("  -o            Store configuration");
+    println!("  -g            Reset to factory default");
+    println!("  -t ms         Timeout to wait for a response (Default: 1000)");
+}
+
+// Numeric option value, or usage and exit when it does not parse or is out of range
+fn parse_value<T: std::str::FromStr + PartialOrd>(option: &str, value: &str, range: std::ops::RangeInclusive<T>) -> T {
+    match value.parse() {
+        Ok(v) if range.contains(&v) => v,
+        _ => {
+            eprintln!("Invalid value '{}' for {}", value, option);
+            usage();
+            std::process::exit(1);
+        }
+    }
+}
+
+// Request selected on the command line; built once all options are parsed, so the
+// IDs and service ID may come before or after it
+enum Command {
+    SetLogLevel(i8),
+    SetDefaultLogLevel(i8),
+    Injection(Vec<u8>),
+    GetLogInfo,
+    Request(ControlRequest),
+}
 
 fn main() {
-    println!("DLT Control Tool (skeleton)");
+    let args: Vec<String> = env::args().collect();
 
-    let mut client = match DltClient::connect("127.0.0.1", 3490) {
+    let mut host = "127.0.0.1".to_string();
+    let mut apid = AppId::default(); // Empty: all applications
+    let mut ctid = ContextId::default();
+    let mut injection_id: Option<u32> = None;
+    let mut timeout_ms = 1000;
+    let mut command: Option<Command> = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        let has_value = i + 1 < args.len();
+        match args[i].as_str() {
+            "-a" if has_value => {
+                apid = args[i + 1].parse().unwrap_or_else(|e| {
+                    eprintln!("Invalid application ID '{}': {}", args[i + 1], e);
+                    std::process::exit(1);
+                });
+                i += 2;
+            }
+            "-c" if has_value => {
+                ctid = args[i + 1].parse().unwrap_or_else(|e| {
+                    eprintln!("Invalid context ID '{}': {}", args[i + 1], e);
+                    std::process::exit(1);
+                });
+                i += 2;
+            }
+            "-l" if has_value => {
+                command = Some(Command::SetLogLevel(parse_value("-l", &args[i + 1], DLT_LOG_LEVEL_DEFAULT..=6)));
+                i += 2;
+            }
+            "-d" if has_value => {
+                command = Some(Command::SetDefaultLogLevel(parse_value("-d", &args[i + 1], DLT_LOG_LEVEL_OFF..=6)));
+                i += 2;
+            }
+            "-s" if has_value => {
+                injection_id = Some(parse_value("-s", &args[i + 1], DLT_SERVICE_ID_INJECTION_MIN..=u32::MAX));
+                i += 2;
+            }
+            "-m" if has_value => {
+                command = Some(Command::Injection(args[i + 1].clone().into_bytes()));
+                i += 2;
+            }
+            "-t" if has_value => {
+                timeout_ms = parse_value("-t", &args[i + 1], 1..=u64::MAX);
+                i += 2;
+            }
+            "-j" => {
+                command = Some(Command::GetLogInfo);
+                i += 1;
+            }
+            "-V" => {
+                command = Some(Command::Request(ControlRequest::GetSoftwareVersion));
+                i += 1;
+            }
+            "-o" => {
+                command = Some(Command::Request(ControlRequest::StoreConfig));
+                i += 1;
+            }
+            "-g" => {
+                command = Some(Command::Request(ControlRequest::ResetToFactoryDefault));
+                i += 1;
+            }
+            "-h" | "--help" => {
+                usage();
+                return;
+            }
+            _ => {
+                host = args[i].clone();
+                i += 1;
+            }
+        }
+    }
+
+    let request = match command {
+        Some(Command::SetLogLevel(log_level)) => ControlRequest::SetLogLevel {
+            apid,
+            ctid,
+            log_level,
+            com_interface: DLT_COM_INTERFACE_REMOTE,
+        },
+        Some(Command::SetDefaultLogLevel(log_level)) => ControlRequest::SetDefaultLogLevel {
+            log_level,
+            com_interface: DLT_COM_INTERFACE_REMOTE,
+        },
+        Some(Command::Injection(data)) => ControlRequest::Injection {
+            service_id: injection_id.unwrap_or(DLT_SERVICE_ID_INJECTION_MIN),
+            data,
+        },
+        Some(Command::GetLogInfo) => ControlRequest::GetLogInfo {
+            options: DLT_GET_LOG_INFO_WITH_DESCRIPTIONS,
+            apid,
+            ctid,
+            com_interface: DLT_COM_INTERFACE_REMOTE,
+        },
+        Some(Command::Request(request)) => request,
+        None => {
+            eprintln!("ERROR: No control request selected");
+            usage();
+            std::process::exit(1);
+        }
+    };
+
+    let mut client = match DltClient::connect(&host, 3490) {
         Ok(c) => c,
         Err(e) => {
             eprintln!("Failed to connect: {}", e);
-            return;
+            std::process::exit(1);
         }
     };
+    if let Err(e) = client.set_read_timeout(Some(Duration::from_millis(timeout_ms))) {
+        eprintln!("Failed to set response timeout: {}", e);
+        std::process::exit(1);
+    }
 
-    println!("Connected to DLT daemon");
-
-    // Example: Send control message
-    let control_msg = b"CONTROL_MESSAGE_EXAMPLE";
-    match client.send_control_message(control_msg) {
-        Ok(_) => println!("Control message sent"),
-        Err(e) => eprintln!("Failed to send control message: {}", e),
+    if let Err(e) = client.send_control_request(&request) {
+        eprintln!("Failed to send control message: {}", e);
+        std::process::exit(1);
     }
+    println!("Sent {}", request);
 
-    // Can also receive responses
-    match client.receive_message() {
-        Ok(Some(msg)) => {
-            println!("Received response: {:?}", msg.payload);
+    // Skip forwarded log traffic until the matching response arrives
+    loop {
+        match client.receive_message() {
+            Ok(Some(msg)) => {
+                match msg.control_response() {
+                    Some(Ok(response)) if response.service_id() == request.service_id() => {
+                        println!("{}", response);
+                        break;
+                    }
+                    Some(Err(e)) => eprintln!("Malformed control response: {}", e),
+                    _ => {}
+                }
+            }
+            Ok(None) => {
+                println!("Connection closed");
+                break;
+            }
+            Err(_) => {
+                eprintln!("No response within {} ms", timeout_ms);
+                break;
+            }
         }
-        Ok(None) => println!("Connection closed"),
-        Err(e) => eprintln!("Error: {}", e),
     }
 }
@@ -0,0 +1,289 @@
+// dlt-convert: print .dlt files in text formats, or cut and concatenate them
+// Rust equivalent of dlt-daemon/src/console/dlt-convert.c
+use dlt_client::{format_message, parse_time, DltMessageFilter, DltOutputFormat, MessageCatalog, DLT_CSV_HEADER};
+use dlt_core::{AppId, ContextId, LogLevel};
+use dlt_storage::{compress_file, DltCompression, DltFileReader, DltFileWriter, DltPcapLinkType, DltPcapWriter};
+use std::env;
+use std::io::{self, BufWriter, Write};
+use std::str::FromStr;
+
+fn usage() {
+    println!("Usage: dlt-convert [options] file1 [file2 ...]");
+    println!("Print or convert the messages of .dlt files (also .dlt.gz and .dlt.zst).");
+    println!("Several files are read one after the other.");
+    println!("Options:");
+    println!("  -F format     Output format: text, hex, ascii, mixed, csv, jsonl (Default: text)");
+    println!("  -x            Same as -F hex");
+    println!("  -a            Same as -F ascii");
+    println!("  -m            Same as -F mixed");
+    println!("  -o file       Write the selected messages to a new .dlt file, not printed");
+    println!("                unless a format is given (.dlt.gz/.dlt.zst: compressed)");
+    println!("  -p file       Write the selected messages to a pcapng file for Wireshark");
+    println!("  -P type       pcapng packets: udp (IPv4/UDP to port 3490) or dlt (Default: udp)");
+    println!("  -A ids        Application IDs (comma separated)");
+    println!("  -C ids        Context IDs (comma separated)");
+    println!("  -l level      Log messages up to this level only (1 fatal .. 6 verbose or name)");
+    println!("  -b index      First message (index counted from 0 over all files)");
+    println!("  -e index      Last message");
+    println!("  -t time       Storage time from (Unix seconds or \"YYYY-MM-DD HH:MM:SS\")");
+    println!("  -T time       Storage time until (exclusive)");
+    println!("  -c catalog    Message catalog (JSON) for non-verbose messages");
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let mut format: Option<DltOutputFormat> = None;
+    let mut output_path: Option<String> = None;
+    let mut pcap_path: Option<String> = None;
+    let mut pcap_link_type = DltPcapLinkType::default();
+    let mut catalog_path: Option<String> = None;
+    let mut filter = DltMessageFilter::new();
+    let mut first_index = 0u64;
+    let mut last_index = u64::MAX;
+    let mut inputs: Vec<String> = Vec::new();
+
+    let mut i = 1;
+    while i < args.len() {
+        let has_value = i + 1 < args.len();
+        match args[i].as_str() {
+            "-F" if has_value => {
+                format = Some(parse_or_exit(&args[i + 1], "format"));
+                i += 2;
+            }
+            "-x" => {
+                format = Some(DltOutputFormat::Hex);
+                i += 1;
+            }
+            "-a" => {
+                format = Some(DltOutputFormat::Ascii);
+                i += 1;
+            }
+            "-m" => {
+                format = Some(DltOutputFormat::Mixed);
+                i += 1;
+            }
+            "-o" if has_value => {
+                output_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-p" if has_value => {
+                pcap_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-P" if has_value => {
+                pcap_link_type = parse_or_exit(&args[i + 1], "link type");
+                i += 2;
+            }
+            "-A" if has_value => {
+                filter.apids = args[i + 1].split(',').map(|id| parse_or_exit::<AppId>(id, "application ID")).collect();
+                i += 2;
+            }
+            "-C" if has_value => {
+                filter.ctids = args[i + 1].split(',').map(|id| parse_or_exit::<ContextId>(id, "context ID")).collect();
+                i += 2;
+            }
+            "-l" if has_value => {
+                filter.max_level = Some(parse_level(&args[i + 1]).unwrap_or_else(|| {
+                    eprintln!("Invalid log level '{}'", args[i + 1]);
+                    std::process::exit(1);
+                }));
+                i += 2;
+            }
+            "-b" if has_value => {
+                first_index = parse_or_exit(&args[i + 1], "message index");
+                i += 2;
+            }
+            "-e" if has_value => {
+                last_index = parse_or_exit(&args[i + 1], "message index");
+                i += 2;
+            }
+            "-t" if has_value => {
+                filter.start_time_us = Some(parse_time_or_exit(&args[i + 1]));
+                i += 2;
+            }
+            "-T" if has_value => {
+                filter.end_time_us = Some(parse_time_or_exit(&args[i + 1]));
+                i += 2;
+            }
+            "-c" if has_value => {
+                catalog_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-h" | "--help" => {
+                usage();
+                return;
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Unknown or incomplete option '{}'", arg);
+                usage();
+                std::process::exit(1);
+            }
+            _ => {
+                inputs.push(args[i].clone());
+                i += 1;
+            }
+        }
+    }
+
+    if inputs.is_empty() {
+        eprintln!("ERROR: No input file");
+        usage();
+        std::process::exit(1);
+    }
+
+    let catalog = catalog_path.map(|path| {
+        MessageCatalog::load(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load catalog {}: {}", path, e);
+            std::process::exit(1);
+        })
+    });
+
+    // A compressed output is written uncompressed first, then compressed in place
+    let (output_path, output_compression) = match output_path {
+        Some(path) => {
+            let (path, compression) = DltCompression::from_archive_path(path);
+            (Some(path), compression)
+        }
+        None => (None, None),
+    };
+    let mut output_file = output_path.as_ref().map(|path| {
+        DltFileWriter::create(path).unwrap_or_else(|e| {
+            eprintln!("Failed to create {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+
+    let mut pcap_file = pcap_path.as_ref().map(|path| {
+        DltPcapWriter::create(path, pcap_link_type).unwrap_or_else(|e| {
+            eprintln!("Failed to create {}: {}", path, e);
+            std::process::exit(1);
+        })
+    });
+
+    // Printed unless only files are written
+    let print_format = match format {
+        Some(format) => Some(format),
+        None if output_file.is_some() || pcap_file.is_some() => None,
+        None => Some(DltOutputFormat::Text),
+    };
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if print_format == Some(DltOutputFormat::Csv) {
+        write_or_exit(&mut out, DLT_CSV_HEADER);
+    }
+
+    let mut index = 0u64;
+    let mut selected = 0u64;
+    'files: for input in &inputs {
+        let mut reader = DltFileReader::open(input).unwrap_or_else(|e| {
+            eprintln!("Failed to open {}: {}", input, e);
+            std::process::exit(1);
+        });
+        while let Some(record) = reader.next_record().unwrap_or_else(|e| {
+            eprintln!("Error reading {}: {}", input, e);
+            std::process::exit(1);
+        }) {
+            for skipped in reader.take_skipped() {
+                eprintln!("{}: resync ({}): {}", input, skipped.reason.kind(), skipped);
+            }
+            let msg = record.message;
+            let current = index;
+            index += 1;
+            if current > last_index {
+                break 'files;
+            }
+            if current < first_index || !filter.matches(&msg) {
+                continue;
+            }
+            selected += 1;
+            if let Some(format) = print_format {
+                write_or_exit(&mut out, &format_message(current, &msg, format, catalog.as_ref()));
+            }
+            if let Some(ref mut file) = output_file {
+                if let Err(e) = file.write_message(&msg) {
+                    eprintln!("Failed to write {}: {}", file.path().display(), e);
+                    std::process::exit(1);
+                }
+            }
+            if let Some(ref mut pcap) = pcap_file {
+                if let Err(e) = pcap.write_message(&msg) {
+                    eprintln!("Failed to write {}: {}", pcap_path.as_deref().unwrap_or_default(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        for skipped in reader.take_skipped() {
+            eprintln!("{}: resync ({}): {}", input, skipped.reason.kind(), skipped);
+        }
+    }
+    if let Err(e) = out.flush() {
+        exit_on_write_error(e);
+    }
+
+    if let Some(mut pcap) = pcap_file {
+        let path = pcap_path.unwrap_or_default();
+        if let Err(e) = pcap.flush() {
+            eprintln!("Failed to write {}: {}", path, e);
+            std::process::exit(1);
+        }
+        eprintln!("{} messages written to {} ({} interfaces)", pcap.packets_written(), path, pcap.interfaces().len());
+    }
+
+    if let Some(mut file) = output_file {
+        if let Err(e) = file.flush() {
+            eprintln!("Failed to write {}: {}", file.path().display(), e);
+            std::process::exit(1);
+        }
+        let path = file.path().to_path_buf();
+        drop(file);
+        let written = match output_compression {
+            Some(compression) => compress_file(&path, compression).unwrap_or_else(|e| {
+                eprintln!("Failed to compress {}: {}", path.display(), e);
+                std::process::exit(1);
+            }),
+            None => path,
+        };
+        eprintln!("{} messages written to {}", selected, written.display());
+    }
+}
+
+fn parse_or_exit<T: FromStr>(value: &str, what: &str) -> T
+where
+    T::Err: std::fmt::Display,
+{
+    value.parse().unwrap_or_else(|e| {
+        eprintln!("Invalid {} '{}': {}", what, value, e);
+        std::process::exit(1);
+    })
+}
+
+fn parse_time_or_exit(value: &str) -> u64 {
+    parse_time(value).unwrap_or_else(|| {
+        eprintln!("Invalid time '{}' (Unix seconds or \"YYYY-MM-DD HH:MM:SS\")", value);
+        std::process::exit(1);
+    })
+}
+
+// 1 .. 6, or fatal, error, warn, info, debug, verbose
+fn parse_level(value: &str) -> Option<LogLevel> {
+    match value.parse::<u8>() {
+        Ok(level) => LogLevel::from_u8(level),
+        Err(_) => (1..=6).filter_map(LogLevel::from_u8).find(|level| level.as_str() == value.to_lowercase()),
+    }
+}
+
+fn write_or_exit(out: &mut impl Write, line: &str) {
+    if let Err(e) = writeln!(out, "{}", line) {
+        exit_on_write_error(e);
+    }
+}
+
+// A closed pipe (dlt-convert ... | head) ends the output quietly
+fn exit_on_write_error(e: io::Error) -> ! {
+    if e.kind() != io::ErrorKind::BrokenPipe {
+        eprintln!("Failed to write output: {}", e);
+        std::process::exit(1);
+    }
+    std::process::exit(0);
+}
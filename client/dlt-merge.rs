@@ -0,0 +1,321 @@
+// dlt-merge: merge .dlt files and live streams into one time-ordered output,
+// or sort files that are out of order (like dlt-daemon/src/console/dlt-sortbytimestamp.c)
+use dlt_client::{
+    format_message, sort_messages, DltClient, DltMergeOrder, DltMerger, DltMessageSource, DltOutputFormat,
+    DltTimeAlignment, MessageCatalog, DLT_CSV_HEADER,
+};
+use dlt_core::{DltMessage, EcuId};
+use dlt_storage::{compress_file, DltCompression, DltFileReader, DltFileWriter, DltPcapLinkType, DltPcapWriter};
+use std::env;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+fn usage() {
+    println!("Usage: dlt-merge [options] file1 [file2 ...]");
+    println!("Merge .dlt files (also .dlt.gz and .dlt.zst) and live streams into one time-ordered output.");
+    println!("Every input must be in order already; use -s for inputs that are not.");
+    println!("Options:");
+    println!("  -o file       Write the merged messages to a new .dlt file, not printed");
+    println!("                unless a format is given (.dlt.gz/.dlt.zst: compressed)");
+    println!("  -p file       Write the merged messages to a pcapng file for Wireshark");
+    println!("  -P type       pcapng packets: udp (IPv4/UDP to port 3490) or dlt (Default: udp)");
+    println!("  -F format     Output format: text, hex, ascii, mixed, csv, jsonl (Default: text)");
+    println!("  -w            Order by message timestamp, aligned per ECU (Default: storage time)");
+    println!("  -O ecu=secs   Offset added to the timestamps of an ECU with -w (Default: estimated");
+    println!("                from the messages as the smallest storage time minus timestamp)");
+    println!("  -s            Read all inputs, then sort them (stable; for inputs out of order)");
+    println!("  -a host       Also merge the messages of a daemon (TCP port 3490, repeatable)");
+    println!("  -t seconds    End a live stream after this long without messages");
+    println!("  -c catalog    Message catalog (JSON) for non-verbose messages");
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let mut format: Option<DltOutputFormat> = None;
+    let mut output_path: Option<String> = None;
+    let mut pcap_path: Option<String> = None;
+    let mut pcap_link_type = DltPcapLinkType::default();
+    let mut order = DltMergeOrder::StorageTime;
+    let mut alignment = DltTimeAlignment::new();
+    let mut sort = false;
+    let mut hosts: Vec<String> = Vec::new();
+    let mut idle_timeout: Option<Duration> = None;
+    let mut catalog_path: Option<String> = None;
+    let mut inputs: Vec<String> = Vec::new();
+
+    let mut i = 1;
+    while i < args.len() {
+        let has_value = i + 1 < args.len();
+        match args[i].as_str() {
+            "-o" if has_value => {
+                output_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-p" if has_value => {
+                pcap_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-P" if has_value => {
+                pcap_link_type = parse_or_exit(&args[i + 1], "link type");
+                i += 2;
+            }
+            "-F" if has_value => {
+                format = Some(parse_or_exit(&args[i + 1], "format"));
+                i += 2;
+            }
+            "-w" => {
+                order = DltMergeOrder::Timestamp;
+                i += 1;
+            }
+            "-O" if has_value => {
+                let (ecu, offset) = parse_offset(&args[i + 1]).unwrap_or_else(|| {
+                    eprintln!("Invalid offset '{}' (ECU=seconds)", args[i + 1]);
+                    std::process::exit(1);
+                });
+                alignment = alignment.with_offset(ecu, offset);
+                i += 2;
+            }
+            "-s" => {
+                sort = true;
+                i += 1;
+            }
+            "-a" if has_value => {
+                hosts.push(args[i + 1].clone());
+                i += 2;
+            }
+            "-t" if has_value => {
+                // Negative, non-finite, zero or overflowing values cannot become a read timeout
+                let secs: f64 = parse_or_exit(&args[i + 1], "timeout");
+                let timeout = Duration::try_from_secs_f64(secs).ok().filter(|timeout| !timeout.is_zero());
+                idle_timeout = Some(timeout.unwrap_or_else(|| {
+                    eprintln!("Invalid timeout '{}' (seconds > 0)", args[i + 1]);
+                    std::process::exit(1);
+                }));
+                i += 2;
+            }
+            "-c" if has_value => {
+                catalog_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-h" | "--help" => {
+                usage();
+                return;
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Unknown or incomplete option '{}'", arg);
+                usage();
+                std::process::exit(1);
+            }
+            _ => {
+                inputs.push(args[i].clone());
+                i += 1;
+            }
+        }
+    }
+
+    if inputs.is_empty() && hosts.is_empty() {
+        eprintln!("ERROR: No input file or daemon");
+        usage();
+        std::process::exit(1);
+    }
+
+    let catalog = catalog_path.map(|path| {
+        MessageCatalog::load(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load catalog {}: {}", path, e);
+            std::process::exit(1);
+        })
+    });
+
+    // Timestamp offsets of the files' ECUs are estimated from all their messages up
+    // front; ECUs only seen live are estimated from their first message
+    if order == DltMergeOrder::Timestamp && !sort {
+        for input in &inputs {
+            for msg in open_file(input) {
+                alignment.observe(&msg.unwrap_or_else(|e| exit_on_read_error(e)));
+            }
+        }
+    }
+
+    // Files first, then daemons; equal keys keep this order
+    let mut sources: Vec<DltMessageSource> = inputs.iter().map(|input| open_file(input)).collect();
+    sources.extend(hosts.iter().map(|host| connect(host, idle_timeout)));
+
+    let mut merger = DltMerger::new(order).with_alignment(alignment.clone());
+    let mut sorted = Vec::new();
+    if sort {
+        for msg in sources.into_iter().flatten() {
+            sorted.push(msg.unwrap_or_else(|e| exit_on_read_error(e)));
+        }
+        sort_messages(&mut sorted, order, &mut alignment);
+    } else {
+        for source in sources {
+            merger.add_source(source);
+        }
+    }
+    let messages: Box<dyn Iterator<Item = DltMessage> + '_> = if sort {
+        Box::new(sorted.into_iter())
+    } else {
+        Box::new(merger.by_ref().map(|msg| msg.unwrap_or_else(|e| exit_on_read_error(e))))
+    };
+
+    let (output_path, output_compression) = match output_path {
+        Some(path) => {
+            let (path, compression) = DltCompression::from_archive_path(path);
+            (Some(path), compression)
+        }
+        None => (None, None),
+    };
+    let mut output_file = output_path.as_ref().map(|path| {
+        DltFileWriter::create(path).unwrap_or_else(|e| {
+            eprintln!("Failed to create {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+
+    let mut pcap_file = pcap_path.as_ref().map(|path| {
+        DltPcapWriter::create(path, pcap_link_type).unwrap_or_else(|e| {
+            eprintln!("Failed to create {}: {}", path, e);
+            std::process::exit(1);
+        })
+    });
+
+    // Printed unless only files are written
+    let print_format = match format {
+        Some(format) => Some(format),
+        None if output_file.is_some() || pcap_file.is_some() => None,
+        None => Some(DltOutputFormat::Text),
+    };
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if print_format == Some(DltOutputFormat::Csv) {
+        write_or_exit(&mut out, DLT_CSV_HEADER);
+    }
+
+    let mut written = 0u64;
+    for msg in messages {
+        if let Some(format) = print_format {
+            write_or_exit(&mut out, &format_message(written, &msg, format, catalog.as_ref()));
+        }
+        if let Some(ref mut file) = output_file {
+            if let Err(e) = file.write_message(&msg) {
+                eprintln!("Failed to write {}: {}", file.path().display(), e);
+                std::process::exit(1);
+            }
+        }
+        if let Some(ref mut pcap) = pcap_file {
+            if let Err(e) = pcap.write_message(&msg) {
+                eprintln!("Failed to write {}: {}", pcap_path.as_deref().unwrap_or_default(), e);
+                std::process::exit(1);
+            }
+        }
+        written += 1;
+    }
+    if let Err(e) = out.flush() {
+        exit_on_write_error(e);
+    }
+
+    if order == DltMergeOrder::Timestamp {
+        if !sort {
+            alignment = merger.alignment().clone();
+        }
+        for (ecu, offset) in alignment.offsets() {
+            let source = if alignment.is_estimated(ecu) { "estimated" } else { "given" };
+            eprintln!("{} timestamp offset {:.6} s ({})", ecu.as_str(), offset as f64 / 1_000_000.0, source);
+        }
+    }
+
+    if let Some(mut pcap) = pcap_file {
+        let path = pcap_path.unwrap_or_default();
+        if let Err(e) = pcap.flush() {
+            eprintln!("Failed to write {}: {}", path, e);
+            std::process::exit(1);
+        }
+        eprintln!("{} messages written to {} ({} interfaces)", pcap.packets_written(), path, pcap.interfaces().len());
+    }
+
+    if let Some(mut file) = output_file {
+        if let Err(e) = file.flush() {
+            eprintln!("Failed to write {}: {}", file.path().display(), e);
+            std::process::exit(1);
+        }
+        let path = file.path().to_path_buf();
+        drop(file);
+        let path = match output_compression {
+            Some(compression) => compress_file(&path, compression).unwrap_or_else(|e| {
+                eprintln!("Failed to compress {}: {}", path.display(), e);
+                std::process::exit(1);
+            }),
+            None => path,
+        };
+        eprintln!("{} messages written to {}", written, path.display());
+    }
+}
+
+// Messages of a file; corrupt records are skipped by the reader
+fn open_file(path: &str) -> DltMessageSource {
+    let reader = DltFileReader::open(path).unwrap_or_else(|e| {
+        eprintln!("Failed to open {}: {}", path, e);
+        std::process::exit(1);
+    });
+    let path = path.to_string();
+    Box::new(reader.map(move |record| record.map(|record| record.message).map_err(|e| source_error(&path, e))))
+}
+
+// Messages of a daemon until it closes the connection or the idle timeout expires
+fn connect(host: &str, idle_timeout: Option<Duration>) -> DltMessageSource {
+    let mut client = DltClient::connect(host, 3490).unwrap_or_else(|e| {
+        eprintln!("Failed to connect to {}:3490: {}", host, e);
+        std::process::exit(1);
+    });
+    if idle_timeout.is_some() {
+        client.set_read_timeout(idle_timeout).expect("Failed to set read timeout");
+    }
+    let host = host.to_string();
+    let messages = client.take_while(|msg| !matches!(msg, Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)));
+    Box::new(messages.map(move |msg| msg.map_err(|e| source_error(&host, e))))
+}
+
+// The merged stream does not tell which source failed, so the error does
+fn source_error(source: &str, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", source, e))
+}
+
+// ECU=seconds, e.g. ECU2=-1.5; finite and at most the span of storage header
+// seconds (u32) either way
+fn parse_offset(value: &str) -> Option<(EcuId, i64)> {
+    let (ecu, secs) = value.split_once('=')?;
+    let secs: f64 = secs.parse().ok().filter(|secs: &f64| secs.is_finite() && secs.abs() <= u32::MAX as f64)?;
+    Some((ecu.parse().ok()?, (secs * 1_000_000.0).round() as i64))
+}
+
+fn parse_or_exit<T: FromStr>(value: &str, what: &str) -> T
+where
+    T::Err: std::fmt::Display,
+{
+    value.parse().unwrap_or_else(|e| {
+        eprintln!("Invalid {} '{}': {}", what, value, e);
+        std::process::exit(1);
+    })
+}
+
+fn exit_on_read_error(e: io::Error) -> ! {
+    eprintln!("Error reading {}", e);
+    std::process::exit(1);
+}
+
+fn write_or_exit(out: &mut impl Write, line: &str) {
+    if let Err(e) = writeln!(out, "{}", line) {
+        exit_on_write_error(e);
+    }
+}
+
+// A closed pipe (dlt-merge ... | head) ends the output quietly
+fn exit_on_write_error(e: io::Error) -> ! {
+    if e.kind() != io::ErrorKind::BrokenPipe {
+        eprintln!("Failed to write output: {}", e);
+        std::process::exit(1);
+    }
+    std::process::exit(0);
+}
@@ -1,23 +1,71 @@
 // dlt-receive: example binary using client library
-use dlt_client::{DltClient, parse_message_text};
+use dlt_client::{DltClient, MessageCatalog, NetworkTraceReassembler, ReassembledTrace, parse_message_text_with_catalog};
+use dlt_core::DltParseErrorStats;
+use dlt_storage::{DltFileWriter, DltPcapLinkType, DltPcapWriter};
+use dlt_transport::DLT_SERIAL_DEFAULT_BAUDRATE;
 use std::env;
+use std::io::ErrorKind;
+use std::time::Duration;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     let mut host = "127.0.0.1".to_string();
+    let mut catalog_path: Option<String> = None;
+    let mut serial_device: Option<String> = None;
+    let mut baudrate = DLT_SERIAL_DEFAULT_BAUDRATE;
+    let mut idle_timeout: Option<Duration> = None;
+    let mut output_path: Option<String> = None;
+    let mut pcap_path: Option<String> = None;
+    let mut pcap_link_type = DltPcapLinkType::default();
 
-    // Parse arguments: -a <address>
+    // Parse arguments: -a <address> -c <catalog.json> -s <tty> -b <baudrate> -t <seconds> -o <file>
+    //                  -p <file.pcapng> -P <udp|dlt>
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
-            "-a" => {
-                if i + 1 < args.len() {
-                    host = args[i + 1].clone();
-                    i += 2;
-                } else {
-                    i += 1;
-                }
+            "-a" if i + 1 < args.len() => {
+                host = args[i + 1].clone();
+                i += 2;
+            }
+            "-c" if i + 1 < args.len() => {
+                catalog_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-s" if i + 1 < args.len() => {
+                serial_device = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-b" if i + 1 < args.len() => {
+                baudrate = args[i + 1].parse().unwrap_or_else(|e| {
+                    eprintln!("Invalid baud rate '{}': {}", args[i + 1], e);
+                    std::process::exit(1);
+                });
+                i += 2;
+            }
+            "-t" if i + 1 < args.len() => {
+                // Negative, non-finite, zero or overflowing values cannot become a read timeout
+                let timeout = args[i + 1].parse().ok().and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+                idle_timeout = Some(timeout.filter(|timeout| !timeout.is_zero()).unwrap_or_else(|| {
+                    eprintln!("Invalid timeout '{}' (seconds > 0)", args[i + 1]);
+                    std::process::exit(1);
+                }));
+                i += 2;
+            }
+            "-o" if i + 1 < args.len() => {
+                output_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-p" if i + 1 < args.len() => {
+                pcap_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-P" if i + 1 < args.len() => {
+                pcap_link_type = args[i + 1].parse().unwrap_or_else(|e| {
+                    eprintln!("Invalid link type: {}", e);
+                    std::process::exit(1);
+                });
+                i += 2;
             }
             _ => {
                 i += 1;
@@ -25,29 +73,175 @@ fn main() {
         }
     }
 
-    println!("Connecting to DLT daemon at {}:3490", host);
+    let catalog = catalog_path.map(|path| {
+        MessageCatalog::load(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load catalog {}: {}", path, e);
+            std::process::exit(1);
+        })
+    });
+
+    // Received messages are also stored, like COVESA dlt-receive -o
+    let mut output_file = output_path.map(|path| {
+        DltFileWriter::create(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to create {}: {}", path, e);
+            std::process::exit(1);
+        })
+    });
+
+    // Also written as packets for Wireshark
+    let mut pcap_file = pcap_path.map(|path| {
+        DltPcapWriter::create(&path, pcap_link_type).unwrap_or_else(|e| {
+            eprintln!("Failed to create {}: {}", path, e);
+            std::process::exit(1);
+        })
+    });
 
-    let mut client = DltClient::connect(&host, 3490)
-        .expect("Failed to connect to daemon");
+    let mut client = match serial_device {
+        Some(device) => {
+            println!("Reading serial DLT from {} at {} baud", device, baudrate);
+            DltClient::open_serial(&device, baudrate).expect("Failed to open serial device")
+        }
+        None => {
+            println!("Connecting to DLT daemon at {}:3490", host);
+            DltClient::connect(&host, 3490).expect("Failed to connect to daemon")
+        }
+    };
+
+    if let Some(timeout) = idle_timeout {
+        client.set_read_timeout(Some(timeout)).expect("Failed to set read timeout");
+    }
 
     println!("Connected. Receiving logs...\n");
 
-    loop {
+    // Framing errors are counted by the client, argument errors here
+    let mut argument_errors = DltParseErrorStats::default();
+    let mut network_traces = NetworkTraceReassembler::new();
+    // Output files are flushed after each batch, so stopping with Ctrl-C loses
+    // nothing already printed. The first write error closes the failed file and
+    // ends the loop; the others are flushed below.
+    let mut write_failed = false;
+
+    'receive: loop {
         match client.receive_messages() {
             Ok(messages) => {
+                for skipped in client.take_skipped() {
+                    eprintln!("Resync ({}): {}", skipped.reason.kind(), skipped);
+                }
+                for gap in client.take_gaps() {
+                    eprintln!("Message counter gap: {}", gap);
+                }
                 if messages.is_empty() {
                     println!("Connection closed");
                     break;
                 }
                 for msg in messages {
-                    let output = parse_message_text(&msg);
+                    if let Err(e) = msg.arguments() {
+                        argument_errors.record(&e);
+                        eprintln!("Bad payload ({}, mcnt {}): {}", e.kind(), msg.standard_header.mcnt, e);
+                    }
+                    if let Some(ref mut file) = output_file {
+                        if let Err(e) = file.write_message(&msg) {
+                            eprintln!("Failed to write {}: {}", file.path().display(), e);
+                            output_file = None;
+                            write_failed = true;
+                            break 'receive;
+                        }
+                    }
+                    if let Some(ref mut pcap) = pcap_file {
+                        if let Err(e) = pcap.write_message(&msg) {
+                            eprintln!("Failed to write pcapng: {}", e);
+                            pcap_file = None;
+                            write_failed = true;
+                            break 'receive;
+                        }
+                    }
+                    let output = parse_message_text_with_catalog(&msg, catalog.as_ref());
                     println!("{}", output);
+                    // Single-message traces are fully shown above
+                    if let Some(trace) = network_traces.push(&msg).filter(|t| t.id.is_some()) {
+                        print_network_trace(&trace);
+                    }
+                    for trace in network_traces.take_evicted() {
+                        print_network_trace(&trace);
+                    }
+                }
+                if let Some(ref mut file) = output_file {
+                    if let Err(e) = file.flush() {
+                        eprintln!("Failed to write {}: {}", file.path().display(), e);
+                        output_file = None;
+                        write_failed = true;
+                        break;
+                    }
+                }
+                if let Some(ref mut pcap) = pcap_file {
+                    if let Err(e) = pcap.flush() {
+                        eprintln!("Failed to write pcapng: {}", e);
+                        pcap_file = None;
+                        write_failed = true;
+                        break;
+                    }
                 }
             }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                println!("No messages for {} s", idle_timeout.unwrap_or_default().as_secs_f64());
+                break;
+            }
             Err(e) => {
                 eprintln!("Error reading: {}", e);
                 break;
             }
         }
     }
+
+    // Sequences whose end message never came
+    network_traces.flush();
+    for trace in network_traces.take_evicted() {
+        print_network_trace(&trace);
+    }
+
+    // Gaps held back for late messages that never came
+    client.flush_gaps();
+    for gap in client.take_gaps() {
+        eprintln!("Message counter gap: {}", gap);
+    }
+
+    if let Some(ref mut pcap) = pcap_file {
+        if let Err(e) = pcap.flush() {
+            eprintln!("Failed to write pcapng: {}", e);
+            write_failed = true;
+        }
+    }
+
+    if let Some(ref mut file) = output_file {
+        if let Err(e) = file.flush() {
+            eprintln!("Failed to write {}: {}", file.path().display(), e);
+            write_failed = true;
+        }
+    }
+
+    let framing_errors = client.parse_error_stats();
+    if framing_errors.total() > 0 || argument_errors.total() > 0 {
+        eprintln!("Parse errors: framing [{}] payload [{}], {} bytes skipped",
+            framing_errors, argument_errors, client.skipped_bytes());
+    }
+
+    // Per application and context, as seen through the message counters
+    let counters = client.counters();
+    for ((ecu, apid, ctid), stats) in counters.sources() {
+        eprintln!("Counters {} {} {}: {}", ecu.as_str(), apid.as_str(), ctid.as_str(), stats);
+    }
+    if counters.total_lost() > 0 {
+        eprintln!("Lost messages: {}", counters.total_lost());
+    }
+    if write_failed {
+        std::process::exit(1);
+    }
+}
+
+// Summary of a segmented trace; an evicted one ended without its end message
+fn print_network_trace(trace: &ReassembledTrace) {
+    println!("Network trace {} #{}: header {} bytes, payload {} bytes, {} segments missing{}",
+        trace.trace_type.as_str(), trace.id.unwrap_or_default(),
+        trace.header.len(), trace.payload.len(), trace.missing_segments,
+        if trace.evicted { ", incomplete (no end message)" } else { "" });
 }
@@ -0,0 +1,181 @@
+// merge: Time-ordered merging of several message sources (used by dlt-merge)
+// DltMerger is a k-way merge: every source must already be in order, and the next
+// message is always the smallest head of all sources. Equal keys keep the order of
+// the sources, and each source keeps its own order, so the merge is stable.
+// sort_messages orders a message set that is not sorted yet, like COVESA
+// dlt-sortbytimestamp.
+use dlt_core::{DltMessage, EcuId, DLT_TIMESTAMP_RESOLUTION};
+use dlt_storage::storage_time_us;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::io;
+
+const MICROSECONDS_PER_TICK: i64 = 1_000_000 / DLT_TIMESTAMP_RESOLUTION as i64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DltMergeOrder {
+    #[default]
+    StorageTime, // Storage header time (when the message was received)
+    Timestamp,   // Standard header timestamp (time since ECU start), aligned per ECU
+}
+
+// Maps each ECU's timestamps to a common time line by adding a per-ECU offset
+// (microseconds). Offsets not set explicitly are estimated as the smallest storage
+// time minus timestamp seen for the ECU, i.e. its start time as seen by the receiver.
+// Messages without a timestamp use their storage time.
+#[derive(Debug, Clone, Default)]
+pub struct DltTimeAlignment {
+    fixed: HashMap<EcuId, i64>,
+    estimated: HashMap<EcuId, i64>,
+}
+
+impl DltTimeAlignment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_offset(mut self, ecu: EcuId, offset_us: i64) -> Self {
+        self.fixed.insert(ecu, offset_us);
+        self
+    }
+
+    // Refine the estimate of the message's ECU
+    pub fn observe(&mut self, msg: &DltMessage) {
+        let Some(timestamp) = msg.standard_header.timestamp else {
+            return;
+        };
+        let offset = storage_time(msg) - timestamp as i64 * MICROSECONDS_PER_TICK;
+        self.estimated
+            .entry(message_ecu(msg))
+            .and_modify(|estimate| *estimate = (*estimate).min(offset))
+            .or_insert(offset);
+    }
+
+    pub fn offset(&self, ecu: EcuId) -> Option<i64> {
+        self.fixed.get(&ecu).or_else(|| self.estimated.get(&ecu)).copied()
+    }
+
+    pub fn is_estimated(&self, ecu: EcuId) -> bool {
+        !self.fixed.contains_key(&ecu) && self.estimated.contains_key(&ecu)
+    }
+
+    // ECUs with an offset, in ID order
+    pub fn offsets(&self) -> Vec<(EcuId, i64)> {
+        let mut ecus: Vec<EcuId> = self.fixed.keys().chain(self.estimated.keys()).copied().collect();
+        ecus.sort();
+        ecus.dedup();
+        ecus.into_iter().filter_map(|ecu| Some((ecu, self.offset(ecu)?))).collect()
+    }
+
+    // Sort key in microseconds; an ECU seen for the first time is estimated from
+    // this message alone
+    pub fn key(&mut self, msg: &DltMessage, order: DltMergeOrder) -> i64 {
+        let timestamp = match (order, msg.standard_header.timestamp) {
+            (DltMergeOrder::Timestamp, Some(timestamp)) => timestamp as i64 * MICROSECONDS_PER_TICK,
+            _ => return storage_time(msg),
+        };
+        let ecu = message_ecu(msg);
+        if self.offset(ecu).is_none() {
+            self.observe(msg);
+        }
+        timestamp.saturating_add(self.offset(ecu).unwrap_or(0))
+    }
+}
+
+fn storage_time(msg: &DltMessage) -> i64 {
+    storage_time_us(msg.storage_header.seconds, msg.storage_header.microseconds) as i64
+}
+
+// The standard header ECU ID, else the storage header's
+fn message_ecu(msg: &DltMessage) -> EcuId {
+    msg.standard_header.ecu.unwrap_or(msg.storage_header.ecu)
+}
+
+pub type DltMessageSource = Box<dyn Iterator<Item = io::Result<DltMessage>>>;
+
+pub struct DltMerger {
+    order: DltMergeOrder,
+    alignment: DltTimeAlignment,
+    sources: Vec<DltMessageSource>,
+    heads: Vec<Option<DltMessage>>,
+    heap: BinaryHeap<Reverse<(i64, usize)>>, // (key, source) of each head
+    started: usize,                          // Sources that delivered their first head
+    refill: Option<usize>,                   // Source of the message returned last
+}
+
+impl DltMerger {
+    pub fn new(order: DltMergeOrder) -> Self {
+        DltMerger {
+            order,
+            alignment: DltTimeAlignment::new(),
+            sources: Vec::new(),
+            heads: Vec::new(),
+            heap: BinaryHeap::new(),
+            started: 0,
+            refill: None,
+        }
+    }
+
+    pub fn with_alignment(mut self, alignment: DltTimeAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    // Sources rank in the order they are added; returns the source number
+    pub fn add_source<I>(&mut self, source: I) -> usize
+    where
+        I: Iterator<Item = io::Result<DltMessage>> + 'static,
+    {
+        self.sources.push(Box::new(source));
+        self.heads.push(None);
+        self.sources.len() - 1
+    }
+
+    pub fn alignment(&self) -> &DltTimeAlignment {
+        &self.alignment
+    }
+
+    // Take the next message of a source as its head; an exhausted source is done
+    fn fill(&mut self, source: usize) -> io::Result<()> {
+        if let Some(msg) = self.sources[source].next().transpose()? {
+            let key = self.alignment.key(&msg, self.order);
+            self.heads[source] = Some(msg);
+            self.heap.push(Reverse((key, source)));
+        }
+        Ok(())
+    }
+}
+
+// A source that fails is not read again; the merge continues with the others
+impl Iterator for DltMerger {
+    type Item = io::Result<DltMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(source) = self.refill.take() {
+            if let Err(e) = self.fill(source) {
+                return Some(Err(e));
+            }
+        }
+        while self.started < self.sources.len() {
+            let source = self.started;
+            self.started += 1;
+            if let Err(e) = self.fill(source) {
+                return Some(Err(e));
+            }
+        }
+        let Reverse((_, source)) = self.heap.pop()?;
+        self.refill = Some(source);
+        self.heads[source].take().map(Ok)
+    }
+}
+
+// Stable sort; with DltMergeOrder::Timestamp the ECU offsets not set in
+// `alignment` are estimated from all messages first
+pub fn sort_messages(messages: &mut [DltMessage], order: DltMergeOrder, alignment: &mut DltTimeAlignment) {
+    if order == DltMergeOrder::Timestamp {
+        for msg in messages.iter() {
+            alignment.observe(msg);
+        }
+    }
+    messages.sort_by_cached_key(|msg| alignment.key(msg, order));
+}
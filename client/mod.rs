@@ -1,11 +1,36 @@
 // client: DLT client library for building receivers, control tools, etc.
-use dlt_core::DltMessage;
-use dlt_transport::{Transport, TcpTransport};
+pub mod catalog;
+pub mod convert;
+pub mod merge;
+pub mod network_trace;
+
+pub use catalog::*;
+pub use convert::*;
+pub use merge::*;
+pub use network_trace::*;
+
+use dlt_core::{
+    AppId, ContextId, ControlRequest, DltCounterGap, DltCounterTracker, DltFrame, DltFrameDecoder,
+    DltMessage, DltMessageRef, DltParseErrorStats, DltSkipped, EcuId, DLT_TIMESTAMP_RESOLUTION,
+};
+use dlt_transport::{SerialTransport, Transport, TcpTransport};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// Identifiers used for control requests sent by client tools
+const DLT_CLIENT_ECU_ID: EcuId = EcuId::new_const("ECU1");
+const DLT_CLIENT_APP_ID: AppId = AppId::new_const("CA1");
+const DLT_CLIENT_CONTEXT_ID: ContextId = ContextId::new_const("CC1");
 
 pub struct DltClient {
     transport: Box<dyn Transport>,
     buffer: Vec<u8>,
-    pending_data: Vec<u8>,
+    decoder: DltFrameDecoder,
+    received: VecDeque<DltMessage>,
+    skipped: Vec<DltSkipped>,   // Not yet taken by the caller
+    parse_error_stats: DltParseErrorStats,
+    counters: DltCounterTracker,
+    gaps: Vec<DltCounterGap>,   // Not yet taken by the caller
 }
 
 impl DltClient {
@@ -17,97 +42,291 @@ impl DltClient {
         Ok(DltClient {
             transport: Box::new(transport),
             buffer: vec![0u8; 65536],
-            pending_data: Vec::new(),
+            decoder: DltFrameDecoder::new(),
+            received: VecDeque::new(),
+            skipped: Vec::new(),
+            parse_error_stats: DltParseErrorStats::default(),
+            counters: DltCounterTracker::new(),
+            gaps: Vec::new(),
+        })
+    }
+
+    // Read messages framed with serial headers from a tty (UART capture or pty)
+    pub fn open_serial(device_path: &str, baudrate: u32) -> std::io::Result<Self> {
+        let mut transport = SerialTransport::new(device_path, baudrate);
+        transport.connect()?;
+
+        Ok(DltClient {
+            transport: Box::new(transport),
+            buffer: vec![0u8; 65536],
+            decoder: DltFrameDecoder::with_serial_header(DLT_CLIENT_ECU_ID),
+            received: VecDeque::new(),
+            skipped: Vec::new(),
+            parse_error_stats: DltParseErrorStats::default(),
+            counters: DltCounterTracker::new(),
+            gaps: Vec::new(),
         })
     }
 
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.transport.set_read_timeout(timeout)
+    }
+
+    // Next single message; Ok(None) once the connection is closed
+    pub fn receive_message(&mut self) -> std::io::Result<Option<DltMessage>> {
+        loop {
+            if let Some(msg) = self.received.pop_front() {
+                return Ok(Some(msg));
+            }
+            if self.read_chunk()? == 0 {
+                return Ok(None);
+            }
+            let messages = self.parse_pending();
+            self.received.extend(messages);
+        }
+    }
+
+    // All messages completed by the next read(s); empty once the connection is closed
     pub fn receive_messages(&mut self) -> std::io::Result<Vec<DltMessage>> {
-        let mut messages = Vec::new();
-        
-        match self.transport.receive(&mut self.buffer) {
-            Ok(0) => return Ok(messages), // Connection closed
-            Ok(n) => {
-                // Append new data to pending data
-                self.pending_data.extend_from_slice(&self.buffer[..n]);
-                
-                // Try to parse all complete messages from pending data
-                let mut offset = 0;
-                while offset < self.pending_data.len() {
-                    // Check if we have enough data for at least storage + standard header
-                    if self.pending_data.len() - offset < 20 {
-                        break;
-                    }
-                    
-                    // Get message length from standard header
-                    let std_len = u16::from_le_bytes([
-                        self.pending_data[offset + 18],
-                        self.pending_data[offset + 19]
-                    ]) as usize;
-                    
-                    // Total message size = storage header (16) + standard header length
-                    let total_len = 16 + std_len;
-                    
-                    // Check if we have the complete message
-                    if offset + total_len > self.pending_data.len() {
-                        break;
+        if !self.received.is_empty() {
+            return Ok(self.received.drain(..).collect());
+        }
+        loop {
+            if self.read_chunk()? == 0 {
+                return Ok(Vec::new()); // Connection closed
+            }
+            let messages = self.parse_pending();
+            if !messages.is_empty() {
+                return Ok(messages);
+            }
+        }
+    }
+
+    // Feed the next transport read to the frame decoder; a closed connection
+    // confirms the gaps held back for late messages
+    fn read_chunk(&mut self) -> std::io::Result<usize> {
+        let n = self.transport.receive(&mut self.buffer)?;
+        self.decoder.push(&self.buffer[..n]);
+        if n == 0 {
+            self.flush_gaps();
+        }
+        Ok(n)
+    }
+
+    // Receive the next chunk and hand each complete message to `f` without copying it.
+    // Returns the number of messages delivered; Ok(0) with nothing pending means closed.
+    pub fn receive_with<F>(&mut self, mut f: F) -> std::io::Result<usize>
+    where
+        F: FnMut(DltMessageRef<'_>),
+    {
+        if self.read_chunk()? == 0 {
+            return Ok(0); // Connection closed
+        }
+        let mut count = 0;
+        while let Some(frame) = self.decoder.next_frame() {
+            match frame {
+                DltFrame::Message(msg) => {
+                    self.gaps.extend(self.counters.observe_message(&msg));
+                    f(msg);
+                    count += 1;
+                }
+                // Version 2 messages are handed out converted to version 1; one
+                // too long for the version 1 headers is dropped
+                DltFrame::MessageV2(msg) => {
+                    let Ok(bytes) = msg.to_v1().map(|msg| msg.to_bytes()) else {
+                        continue;
+                    };
+                    if let Ok(msg) = DltMessageRef::from_bytes(&bytes) {
+                        self.gaps.extend(self.counters.observe_message(&msg));
+                        f(msg);
+                        count += 1;
                     }
-                    
-                    // Parse the message
-                    if let Some(msg) = DltMessage::from_bytes(&self.pending_data[offset..offset + total_len]) {
-                        messages.push(msg);
+                }
+                DltFrame::Skipped(skipped) => {
+                    self.parse_error_stats.record(&skipped.reason);
+                    self.skipped.push(skipped);
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    // Decode all complete messages received so far
+    fn parse_pending(&mut self) -> Vec<DltMessage> {
+        let mut messages = Vec::new();
+        while let Some(frame) = self.decoder.next_frame() {
+            match frame {
+                DltFrame::Message(msg) => {
+                    self.gaps.extend(self.counters.observe_message(&msg));
+                    messages.push(msg.to_message());
+                }
+                DltFrame::MessageV2(msg) => {
+                    let Ok(msg) = msg.to_v1() else {
+                        continue;
+                    };
+                    if let Ok(msg) = DltMessageRef::from_bytes(&msg.to_bytes()) {
+                        self.gaps.extend(self.counters.observe_message(&msg));
                     }
-                    
-                    offset += total_len;
+                    messages.push(msg);
                 }
-                
-                // Remove processed data from pending buffer
-                if offset > 0 {
-                    self.pending_data.drain(0..offset);
+                DltFrame::Skipped(skipped) => {
+                    self.parse_error_stats.record(&skipped.reason);
+                    self.skipped.push(skipped);
                 }
             }
-            Err(e) => return Err(e),
         }
-        
-        Ok(messages)
+        messages
+    }
+
+    // Byte ranges dropped while resynchronizing since the last call
+    pub fn take_skipped(&mut self) -> Vec<DltSkipped> {
+        std::mem::take(&mut self.skipped)
+    }
+
+    // Framing error counters since connect (one per skipped range)
+    pub fn parse_error_stats(&self) -> &DltParseErrorStats {
+        &self.parse_error_stats
+    }
+
+    // Total bytes dropped while resynchronizing
+    pub fn skipped_bytes(&self) -> u64 {
+        self.decoder.skipped_bytes()
+    }
+
+    // Message counter gaps detected since the last call. A gap is held back until
+    // 128 later messages of its source have arrived or the connection is closed.
+    pub fn take_gaps(&mut self) -> Vec<DltCounterGap> {
+        std::mem::take(&mut self.gaps)
+    }
+
+    // Confirm the gaps still held back, e.g. when giving up after a read timeout
+    pub fn flush_gaps(&mut self) {
+        self.gaps.extend(self.counters.flush());
+    }
+
+    // Per-source message counters and lost messages since connect
+    pub fn counters(&self) -> &DltCounterTracker {
+        &self.counters
     }
 
     pub fn send_control_message(&mut self, data: &[u8]) -> std::io::Result<()> {
         self.transport.send(data)?;
         Ok(())
     }
+
+    pub fn send_control_request(&mut self, request: &ControlRequest) -> std::io::Result<()> {
+        let msg = DltMessage::new_control_request(
+            DLT_CLIENT_ECU_ID,
+            DLT_CLIENT_APP_ID,
+            DLT_CLIENT_CONTEXT_ID,
+            request,
+        )
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+        self.send_control_message(&msg.to_bytes())
+    }
+}
+
+// Received messages until the connection is closed (see receive_message)
+impl Iterator for DltClient {
+    type Item = std::io::Result<DltMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receive_message().transpose()
+    }
 }
 
 pub fn parse_message_text(msg: &DltMessage) -> String {
+    parse_message_text_with_catalog(msg, None)
+}
+
+// Same as parse_message_text, resolving non-verbose messages through a catalog
+pub fn parse_message_text_with_catalog(msg: &DltMessage, catalog: Option<&MessageCatalog>) -> String {
+    match message_header_text(msg, catalog) {
+        Some(header) => format!("{} [{}]", header, message_payload_text(msg, catalog)),
+        None => format!("[{}] <no extended header>", msg.storage_header.seconds),
+    }
+}
+
+// Application ID, context ID and argument count, from the extended header or the
+// catalog entry of a non-verbose message; None if neither is available
+pub fn message_ids(msg: &DltMessage, catalog: Option<&MessageCatalog>) -> Option<(String, String, u8)> {
+    let entry = msg.message_id().and_then(|id| catalog?.get(id));
+    match (&msg.extended_header, entry) {
+        (Some(ext), _) => Some((ext.apid.as_str(), ext.ctid.as_str(), ext.noar)),
+        (None, Some(entry)) => Some((
+            entry.apid.map(|id| id.as_str()).unwrap_or_default(),
+            entry.ctid.map(|id| id.as_str()).unwrap_or_default(),
+            0,
+        )),
+        (None, None) => None,
+    }
+}
+
+// Receive date and time from the storage header: "YYYY/MM/DD HH:MM:SS.uuuuuu"
+pub fn storage_time_text(msg: &DltMessage) -> String {
     use std::time::{UNIX_EPOCH, Duration};
-    
-    if let Some(ref ext) = msg.extended_header {
-        let apid = ext.apid.as_str();
-        let ctid = ext.ctid.as_str();
-        let text = msg.extract_string_payload().unwrap_or_default();
-
-        // Format date/time from storage header
-        let secs = msg.storage_header.seconds;
-        let usecs = msg.storage_header.microseconds;
-        
-        let datetime = UNIX_EPOCH + Duration::from_secs(secs as u64);
-        let datetime = chrono::DateTime::<chrono::Local>::from(datetime);
-        let date_str = datetime.format("%Y/%m/%d %H:%M:%S").to_string();
-
-        // ECU ID
-        let ecu = String::from_utf8_lossy(&msg.storage_header.ecu.0)
-            .trim_end_matches('\0')
-            .to_string();
-
-        // Message counter
-        let mcnt = msg.standard_header.mcnt;
-        
-        // Number of arguments from extended header
-        let noar = ext.noar;
-
-        // Format: "YYYY/MM/DD HH:MM:SS.uuuuuu   timestamp mcnt ECU APID CTID log level V/N noar [payload]"
-        format!("{}.{:06}   {:10} {:03} {} {:<4} {:<4} log warn V {} [{}]",
-            date_str, usecs, secs, mcnt, ecu, apid, ctid, noar, text)
+
+    let datetime = UNIX_EPOCH + Duration::from_secs(msg.storage_header.seconds as u64);
+    let datetime = chrono::DateTime::<chrono::Local>::from(datetime);
+    format!("{}.{:06}", datetime.format("%Y/%m/%d %H:%M:%S"), msg.storage_header.microseconds)
+}
+
+// Everything parse_message_text prints before the payload:
+// "YYYY/MM/DD HH:MM:SS.uuuuuu   timestamp mcnt ECU APID CTID log level V/N noar"
+pub fn message_header_text(msg: &DltMessage, catalog: Option<&MessageCatalog>) -> Option<String> {
+    let (apid, ctid, noar) = message_ids(msg, catalog)?;
+
+    let info = msg.message_info()
+        .map(|info| info.to_string())
+        .unwrap_or_else(|| "- - N".to_string());
+
+    // ECU ID
+    let ecu = String::from_utf8_lossy(&msg.storage_header.ecu.0)
+        .trim_end_matches('\0')
+        .to_string();
+
+    // Message counter
+    let mcnt = msg.standard_header.mcnt;
+
+    // Sender timestamp (WTMS) in seconds with 0.1 ms resolution, 0.0000 if not sent
+    let tmsp = msg.standard_header.timestamp.unwrap_or(0);
+    let tmsp_secs = tmsp / DLT_TIMESTAMP_RESOLUTION;
+    let tmsp_frac = tmsp % DLT_TIMESTAMP_RESOLUTION;
+
+    Some(format!("{} {:10}.{:04} {:03} {} {:<4} {:<4} {} {}",
+        storage_time_text(msg), tmsp_secs, tmsp_frac, mcnt, ecu, apid, ctid, info, noar))
+}
+
+// Payload as printed by parse_message_text: the verbose arguments, or the
+// non-verbose rendering
+pub fn message_payload_text(msg: &DltMessage, catalog: Option<&MessageCatalog>) -> String {
+    if msg.is_verbose() {
+        msg.extract_string_payload().unwrap_or_default()
     } else {
-        format!("[{}] <no extended header>", msg.storage_header.seconds)
+        non_verbose_text(msg, catalog)
+    }
+}
+
+// Non-verbose payload: catalog rendering, or "[id] hex bytes" like the COVESA tools
+fn non_verbose_text(msg: &DltMessage, catalog: Option<&MessageCatalog>) -> String {
+    match msg.control_response() {
+        Some(Ok(response)) => return response.to_string(),
+        Some(Err(e)) => return format!("<control response: {}>", e),
+        None => {}
+    }
+    match msg.control_request() {
+        Some(Ok(request)) => return request.to_string(),
+        Some(Err(e)) => return format!("<control request: {}>", e),
+        None => {}
+    }
+    if let Some(text) = catalog.and_then(|c| c.render(msg)) {
+        return text;
+    }
+    match (msg.message_id(), msg.non_verbose_data()) {
+        (Some(id), Some(data)) => {
+            let hex: Vec<String> = data.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("[{}] {}", id, hex.join(" "))
+        }
+        _ => String::new(),
     }
 }
@@ -0,0 +1,176 @@
+// network_trace: Reassembly of segmented network trace messages
+// A sequence is keyed by ECU, application, context and trace ID, so traces from
+// several senders may interleave. Segments without a start message are dropped.
+// Pending sequences are bounded in number and buffered bytes: when a limit is
+// reached (e.g. after a lost end message) the oldest ones are evicted and reported
+// incomplete through take_evicted.
+use dlt_core::*;
+use std::collections::HashMap;
+
+// Default limits of pending sequences
+pub const DLT_NW_TRACE_MAX_PENDING: usize = 64;
+pub const DLT_NW_TRACE_MAX_PENDING_BYTES: usize = 16 * 1024 * 1024;
+
+// Complete network frame, from a single message or a finished segmented sequence
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReassembledTrace {
+    pub trace_type: NetworkTraceType,
+    pub header: Vec<u8>,
+    pub payload: Vec<u8>,
+    pub id: Option<u32>,        // Trace ID of a segmented sequence
+    pub missing_segments: u16,  // Segments not received before the end message (or eviction)
+    pub evicted: bool,          // Given up before its end message arrived
+}
+
+struct PendingTrace {
+    trace_type: NetworkTraceType,
+    header: Vec<u8>,
+    payload_len: u32,
+    segments: Vec<Option<Vec<u8>>>,
+    bytes: usize,  // Header and segment data held
+    started: u64,  // Start order, the oldest is evicted first
+}
+
+impl PendingTrace {
+    fn finish(self, id: u32, evicted: bool) -> ReassembledTrace {
+        let missing_segments = self.segments.iter().filter(|s| s.is_none()).count() as u16;
+        let mut payload: Vec<u8> = self.segments.into_iter().flatten().flatten().collect();
+        payload.truncate(self.payload_len as usize);
+        ReassembledTrace {
+            trace_type: self.trace_type,
+            header: self.header,
+            payload,
+            id: Some(id),
+            missing_segments,
+            evicted,
+        }
+    }
+}
+
+type TraceKey = (String, AppId, ContextId, u32);
+
+pub struct NetworkTraceReassembler {
+    pending: HashMap<TraceKey, PendingTrace>,
+    pending_bytes: usize,
+    started: u64,
+    max_pending: usize,
+    max_pending_bytes: usize,
+    evicted: Vec<ReassembledTrace>,
+}
+
+impl NetworkTraceReassembler {
+    pub fn new() -> Self {
+        Self::with_limits(DLT_NW_TRACE_MAX_PENDING, DLT_NW_TRACE_MAX_PENDING_BYTES)
+    }
+
+    // At most `max_pending` sequences holding `max_pending_bytes` in total
+    pub fn with_limits(max_pending: usize, max_pending_bytes: usize) -> Self {
+        NetworkTraceReassembler {
+            pending: HashMap::new(),
+            pending_bytes: 0,
+            started: 0,
+            max_pending,
+            max_pending_bytes,
+            evicted: Vec::new(),
+        }
+    }
+
+    // Feed one message; returns a frame once it is complete
+    // Messages that are not network traces are ignored
+    pub fn push(&mut self, msg: &DltMessage) -> Option<ReassembledTrace> {
+        let (trace_type, trace) = msg.network_trace()?;
+        let ext = msg.extended_header.as_ref()?;
+        let key = |id| (msg.storage_header.ecu.as_str(), ext.apid, ext.ctid, id);
+
+        match trace {
+            NetworkTrace::Frame { header, payload } => Some(ReassembledTrace {
+                trace_type,
+                header,
+                payload,
+                id: None,
+                missing_segments: 0,
+                evicted: false,
+            }),
+            NetworkTrace::Start { id, header, payload_len, segment_count, .. } => {
+                // A restarted sequence ends the previous one
+                self.evict(&key(id));
+                self.started += 1;
+                let pending = PendingTrace {
+                    trace_type,
+                    bytes: header.len(),
+                    header,
+                    payload_len,
+                    segments: vec![None; segment_count as usize],
+                    started: self.started,
+                };
+                self.pending_bytes += pending.bytes;
+                self.pending.insert(key(id), pending);
+                self.enforce_limits();
+                None
+            }
+            NetworkTrace::Segment { id, sequence, data } => {
+                let pending = self.pending.get_mut(&key(id))?;
+                if let Some(slot) = pending.segments.get_mut(sequence as usize) {
+                    let added = data.len();
+                    let replaced = slot.replace(data).map_or(0, |old| old.len());
+                    pending.bytes = pending.bytes + added - replaced;
+                    self.pending_bytes = self.pending_bytes + added - replaced;
+                    self.enforce_limits();
+                }
+                None
+            }
+            NetworkTrace::End { id } => {
+                let pending = self.pending.remove(&key(id))?;
+                self.pending_bytes -= pending.bytes;
+                Some(pending.finish(id, false))
+            }
+        }
+    }
+
+    // Sequences started but not yet ended
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Header and segment bytes held by pending sequences
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes
+    }
+
+    // Sequences evicted since the last call, with the segments received so far
+    pub fn take_evicted(&mut self) -> Vec<ReassembledTrace> {
+        std::mem::take(&mut self.evicted)
+    }
+
+    // End of input: evict all pending sequences (then see take_evicted)
+    pub fn flush(&mut self) {
+        let mut keys: Vec<(u64, TraceKey)> = self.pending.iter().map(|(key, p)| (p.started, key.clone())).collect();
+        keys.sort();
+        for (_, key) in keys {
+            self.evict(&key);
+        }
+    }
+
+    // Evict the oldest sequences until both limits hold
+    fn enforce_limits(&mut self) {
+        while self.pending.len() > self.max_pending || self.pending_bytes > self.max_pending_bytes {
+            let Some(oldest) = self.pending.iter().min_by_key(|(_, p)| p.started).map(|(key, _)| key.clone()) else {
+                break;
+            };
+            self.evict(&oldest);
+        }
+    }
+
+    fn evict(&mut self, key: &TraceKey) {
+        if let Some(pending) = self.pending.remove(key) {
+            self.pending_bytes -= pending.bytes;
+            self.evicted.push(pending.finish(key.3, true));
+        }
+    }
+}
+
+impl Default for NetworkTraceReassembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,23 @@
+// Wall-clock source for storage header timestamps
+// With the "std" feature the system clock is used by default; without it only the
+// constructors taking a clock exist, so every storage header gets a real time.
+
+// Time since the Unix epoch as stored in the storage header
+pub trait DltClock {
+    // (seconds, microseconds)
+    fn now(&self) -> (u32, u32);
+}
+
+// SystemTime based clock (default with the "std" feature)
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl DltClock for SystemClock {
+    fn now(&self) -> (u32, u32) {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        (now.as_secs() as u32, now.subsec_micros())
+    }
+}
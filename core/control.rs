@@ -0,0 +1,497 @@
+// Control messages: service ID based requests and responses (MSTP = Control)
+use crate::error::{DltEncodeError, DltParseError};
+use crate::types::{AppId, ContextId};
+use crate::verbose::{PayloadReader, PayloadWriter};
+use core::fmt;
+use alloc::{string::{String, ToString}, vec::Vec};
+
+pub const DLT_SERVICE_ID_SET_LOG_LEVEL: u32 = 0x01;
+pub const DLT_SERVICE_ID_SET_TRACE_STATUS: u32 = 0x02;
+pub const DLT_SERVICE_ID_GET_LOG_INFO: u32 = 0x03;
+pub const DLT_SERVICE_ID_GET_DEFAULT_LOG_LEVEL: u32 = 0x04;
+pub const DLT_SERVICE_ID_STORE_CONFIG: u32 = 0x05;
+pub const DLT_SERVICE_ID_RESET_TO_FACTORY_DEFAULT: u32 = 0x06;
+pub const DLT_SERVICE_ID_SET_COM_INTERFACE_STATUS: u32 = 0x07;
+pub const DLT_SERVICE_ID_SET_MESSAGE_FILTERING: u32 = 0x0a;
+pub const DLT_SERVICE_ID_SET_TIMING_PACKETS: u32 = 0x0b;
+pub const DLT_SERVICE_ID_SET_DEFAULT_LOG_LEVEL: u32 = 0x11;
+pub const DLT_SERVICE_ID_GET_SOFTWARE_VERSION: u32 = 0x13;
+pub const DLT_SERVICE_ID_GET_LOG_CHANNEL_NAMES: u32 = 0x17;
+pub const DLT_SERVICE_ID_UNREGISTER_CONTEXT: u32 = 0xf01;
+pub const DLT_SERVICE_ID_MARKER: u32 = 0xf04;
+pub const DLT_SERVICE_ID_INJECTION_MIN: u32 = 0xfff; // Application-defined injections
+
+// Communication interface name used by the daemon for its remote (TCP) side
+pub const DLT_COM_INTERFACE_REMOTE: [u8; 4] = *b"remo";
+
+// Log level / trace status values outside the LogLevel enum
+pub const DLT_LOG_LEVEL_DEFAULT: i8 = -1;
+pub const DLT_LOG_LEVEL_OFF: i8 = 0;
+
+// GetLogInfo options (request) and matching response status values
+pub const DLT_GET_LOG_INFO_IDS: u8 = 3;
+pub const DLT_GET_LOG_INFO_WITH_LEVEL: u8 = 4;
+pub const DLT_GET_LOG_INFO_WITH_TRACE: u8 = 5;
+pub const DLT_GET_LOG_INFO_WITH_BOTH: u8 = 6;
+pub const DLT_GET_LOG_INFO_WITH_DESCRIPTIONS: u8 = 7;
+pub const DLT_GET_LOG_INFO_NO_MATCH: u8 = 8;
+pub const DLT_GET_LOG_INFO_OVERFLOW: u8 = 9;
+
+// Name of a service ID as printed by the COVESA tools
+pub fn service_name(service_id: u32) -> &'static str {
+    match service_id {
+        DLT_SERVICE_ID_SET_LOG_LEVEL => "set_log_level",
+        DLT_SERVICE_ID_SET_TRACE_STATUS => "set_trace_status",
+        DLT_SERVICE_ID_GET_LOG_INFO => "get_log_info",
+        DLT_SERVICE_ID_GET_DEFAULT_LOG_LEVEL => "get_default_log_level",
+        DLT_SERVICE_ID_STORE_CONFIG => "store_config",
+        DLT_SERVICE_ID_RESET_TO_FACTORY_DEFAULT => "reset_to_factory_default",
+        DLT_SERVICE_ID_SET_COM_INTERFACE_STATUS => "set_com_interface_status",
+        DLT_SERVICE_ID_SET_MESSAGE_FILTERING => "set_message_filtering",
+        DLT_SERVICE_ID_SET_TIMING_PACKETS => "set_timing_packets",
+        DLT_SERVICE_ID_SET_DEFAULT_LOG_LEVEL => "set_default_log_level",
+        DLT_SERVICE_ID_GET_SOFTWARE_VERSION => "get_software_version",
+        DLT_SERVICE_ID_GET_LOG_CHANNEL_NAMES => "get_log_channel_names",
+        DLT_SERVICE_ID_UNREGISTER_CONTEXT => "unregister_context",
+        DLT_SERVICE_ID_MARKER => "marker",
+        id if id >= DLT_SERVICE_ID_INJECTION_MIN => "injection",
+        _ => "unknown",
+    }
+}
+
+// Response status byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlStatus {
+    Ok,
+    NotSupported,
+    Error,
+    // GetLogInfo specific values (3..9)
+    Other(u8),
+}
+
+impl ControlStatus {
+    pub fn from_u8(val: u8) -> Self {
+        match val {
+            0 => ControlStatus::Ok,
+            1 => ControlStatus::NotSupported,
+            2 => ControlStatus::Error,
+            other => ControlStatus::Other(other),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            ControlStatus::Ok => 0,
+            ControlStatus::NotSupported => 1,
+            ControlStatus::Error => 2,
+            ControlStatus::Other(val) => val,
+        }
+    }
+}
+
+impl fmt::Display for ControlStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControlStatus::Ok => write!(f, "ok"),
+            ControlStatus::NotSupported => write!(f, "not_supported"),
+            ControlStatus::Error => write!(f, "error"),
+            ControlStatus::Other(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+// Per-context entry of a GetLogInfo response
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextLogInfo {
+    pub ctid: ContextId,
+    pub log_level: Option<i8>,
+    pub trace_status: Option<i8>,
+    pub description: Option<String>,
+}
+
+// Per-application entry of a GetLogInfo response
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppLogInfo {
+    pub apid: AppId,
+    pub contexts: Vec<ContextLogInfo>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlRequest {
+    SetLogLevel { apid: AppId, ctid: ContextId, log_level: i8, com_interface: [u8; 4] },
+    SetTraceStatus { apid: AppId, ctid: ContextId, trace_status: i8, com_interface: [u8; 4] },
+    GetLogInfo { options: u8, apid: AppId, ctid: ContextId, com_interface: [u8; 4] },
+    GetDefaultLogLevel,
+    StoreConfig,
+    ResetToFactoryDefault,
+    SetComInterfaceStatus { com_interface: [u8; 4], status: u8 },
+    SetMessageFiltering { enabled: bool },
+    SetTimingPackets { enabled: bool },
+    SetDefaultLogLevel { log_level: i8, com_interface: [u8; 4] },
+    GetSoftwareVersion,
+    GetLogChannelNames,
+    Marker,
+    Injection { service_id: u32, data: Vec<u8> },
+}
+
+impl ControlRequest {
+    pub fn service_id(&self) -> u32 {
+        match self {
+            ControlRequest::SetLogLevel { .. } => DLT_SERVICE_ID_SET_LOG_LEVEL,
+            ControlRequest::SetTraceStatus { .. } => DLT_SERVICE_ID_SET_TRACE_STATUS,
+            ControlRequest::GetLogInfo { .. } => DLT_SERVICE_ID_GET_LOG_INFO,
+            ControlRequest::GetDefaultLogLevel => DLT_SERVICE_ID_GET_DEFAULT_LOG_LEVEL,
+            ControlRequest::StoreConfig => DLT_SERVICE_ID_STORE_CONFIG,
+            ControlRequest::ResetToFactoryDefault => DLT_SERVICE_ID_RESET_TO_FACTORY_DEFAULT,
+            ControlRequest::SetComInterfaceStatus { .. } => DLT_SERVICE_ID_SET_COM_INTERFACE_STATUS,
+            ControlRequest::SetMessageFiltering { .. } => DLT_SERVICE_ID_SET_MESSAGE_FILTERING,
+            ControlRequest::SetTimingPackets { .. } => DLT_SERVICE_ID_SET_TIMING_PACKETS,
+            ControlRequest::SetDefaultLogLevel { .. } => DLT_SERVICE_ID_SET_DEFAULT_LOG_LEVEL,
+            ControlRequest::GetSoftwareVersion => DLT_SERVICE_ID_GET_SOFTWARE_VERSION,
+            ControlRequest::GetLogChannelNames => DLT_SERVICE_ID_GET_LOG_CHANNEL_NAMES,
+            ControlRequest::Marker => DLT_SERVICE_ID_MARKER,
+            ControlRequest::Injection { service_id, .. } => *service_id,
+        }
+    }
+
+    pub fn to_bytes(&self, big_endian: bool) -> Result<Vec<u8>, DltEncodeError> {
+        let mut w = PayloadWriter::new(big_endian);
+        w.u32(self.service_id());
+        match self {
+            ControlRequest::SetLogLevel { apid, ctid, log_level: level, com_interface }
+            | ControlRequest::SetTraceStatus { apid, ctid, trace_status: level, com_interface } => {
+                w.bytes(&apid.0);
+                w.bytes(&ctid.0);
+                w.bytes(&[*level as u8]);
+                w.bytes(com_interface);
+            }
+            ControlRequest::GetLogInfo { options, apid, ctid, com_interface } => {
+                w.bytes(&[*options]);
+                w.bytes(&apid.0);
+                w.bytes(&ctid.0);
+                w.bytes(com_interface);
+            }
+            ControlRequest::SetComInterfaceStatus { com_interface, status } => {
+                w.bytes(com_interface);
+                w.bytes(&[*status]);
+            }
+            ControlRequest::SetMessageFiltering { enabled }
+            | ControlRequest::SetTimingPackets { enabled } => {
+                w.bytes(&[*enabled as u8]);
+            }
+            ControlRequest::SetDefaultLogLevel { log_level, com_interface } => {
+                w.bytes(&[*log_level as u8]);
+                w.bytes(com_interface);
+            }
+            ControlRequest::Injection { data, .. } => {
+                w.u32(len_u32(data.len())?);
+                w.bytes(data);
+            }
+            ControlRequest::GetDefaultLogLevel
+            | ControlRequest::StoreConfig
+            | ControlRequest::ResetToFactoryDefault
+            | ControlRequest::GetSoftwareVersion
+            | ControlRequest::GetLogChannelNames
+            | ControlRequest::Marker => {}
+        }
+        Ok(w.finish())
+    }
+
+    pub fn from_bytes(payload: &[u8], big_endian: bool) -> Result<Self, DltParseError> {
+        let mut r = PayloadReader::new(payload, big_endian);
+        let service_id = r.u32()?;
+        let request = match service_id {
+            DLT_SERVICE_ID_SET_LOG_LEVEL => ControlRequest::SetLogLevel {
+                apid: AppId(r.id()?),
+                ctid: ContextId(r.id()?),
+                log_level: r.u8()? as i8,
+                com_interface: r.id()?,
+            },
+            DLT_SERVICE_ID_SET_TRACE_STATUS => ControlRequest::SetTraceStatus {
+                apid: AppId(r.id()?),
+                ctid: ContextId(r.id()?),
+                trace_status: r.u8()? as i8,
+                com_interface: r.id()?,
+            },
+            DLT_SERVICE_ID_GET_LOG_INFO => ControlRequest::GetLogInfo {
+                options: r.u8()?,
+                apid: AppId(r.id()?),
+                ctid: ContextId(r.id()?),
+                com_interface: r.id()?,
+            },
+            DLT_SERVICE_ID_GET_DEFAULT_LOG_LEVEL => ControlRequest::GetDefaultLogLevel,
+            DLT_SERVICE_ID_STORE_CONFIG => ControlRequest::StoreConfig,
+            DLT_SERVICE_ID_RESET_TO_FACTORY_DEFAULT => ControlRequest::ResetToFactoryDefault,
+            DLT_SERVICE_ID_SET_COM_INTERFACE_STATUS => ControlRequest::SetComInterfaceStatus {
+                com_interface: r.id()?,
+                status: r.u8()?,
+            },
+            DLT_SERVICE_ID_SET_MESSAGE_FILTERING => ControlRequest::SetMessageFiltering {
+                enabled: r.u8()? != 0,
+            },
+            DLT_SERVICE_ID_SET_TIMING_PACKETS => ControlRequest::SetTimingPackets {
+                enabled: r.u8()? != 0,
+            },
+            DLT_SERVICE_ID_SET_DEFAULT_LOG_LEVEL => ControlRequest::SetDefaultLogLevel {
+                log_level: r.u8()? as i8,
+                com_interface: r.id()?,
+            },
+            DLT_SERVICE_ID_GET_SOFTWARE_VERSION => ControlRequest::GetSoftwareVersion,
+            DLT_SERVICE_ID_GET_LOG_CHANNEL_NAMES => ControlRequest::GetLogChannelNames,
+            DLT_SERVICE_ID_MARKER => ControlRequest::Marker,
+            id if id >= DLT_SERVICE_ID_INJECTION_MIN => {
+                let len = r.u32()? as usize;
+                ControlRequest::Injection { service_id: id, data: r.take(len)?.to_vec() }
+            }
+            _ => return Err(DltParseError::UnknownServiceId { offset: 0, service_id }),
+        };
+        Ok(request)
+    }
+}
+
+impl fmt::Display for ControlRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]", service_name(self.service_id()))?;
+        match self {
+            ControlRequest::SetLogLevel { apid, ctid, log_level, .. } => {
+                write!(f, " {} {} {}", apid.as_str(), ctid.as_str(), log_level)
+            }
+            ControlRequest::SetTraceStatus { apid, ctid, trace_status, .. } => {
+                write!(f, " {} {} {}", apid.as_str(), ctid.as_str(), trace_status)
+            }
+            ControlRequest::GetLogInfo { options, apid, ctid, .. } => {
+                write!(f, " {} {} {}", options, apid.as_str(), ctid.as_str())
+            }
+            ControlRequest::SetDefaultLogLevel { log_level, .. } => write!(f, " {}", log_level),
+            ControlRequest::SetMessageFiltering { enabled }
+            | ControlRequest::SetTimingPackets { enabled } => write!(f, " {}", *enabled as u8),
+            ControlRequest::Injection { data, .. } => write!(f, " {} bytes", data.len()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlResponse {
+    // Status-only response (SetLogLevel, StoreConfig, Marker, Injection, ...)
+    Status { service_id: u32, status: ControlStatus },
+    GetLogInfo { status: ControlStatus, apps: Vec<AppLogInfo>, com_interface: [u8; 4] },
+    GetDefaultLogLevel { status: ControlStatus, log_level: i8 },
+    GetSoftwareVersion { status: ControlStatus, version: String },
+    GetLogChannelNames { status: ControlStatus, names: Vec<[u8; 4]> },
+    UnregisterContext { status: ControlStatus, apid: AppId, ctid: ContextId, com_interface: [u8; 4] },
+}
+
+impl ControlResponse {
+    pub fn service_id(&self) -> u32 {
+        match self {
+            ControlResponse::Status { service_id, .. } => *service_id,
+            ControlResponse::GetLogInfo { .. } => DLT_SERVICE_ID_GET_LOG_INFO,
+            ControlResponse::GetDefaultLogLevel { .. } => DLT_SERVICE_ID_GET_DEFAULT_LOG_LEVEL,
+            ControlResponse::GetSoftwareVersion { .. } => DLT_SERVICE_ID_GET_SOFTWARE_VERSION,
+            ControlResponse::GetLogChannelNames { .. } => DLT_SERVICE_ID_GET_LOG_CHANNEL_NAMES,
+            ControlResponse::UnregisterContext { .. } => DLT_SERVICE_ID_UNREGISTER_CONTEXT,
+        }
+    }
+
+    pub fn status(&self) -> ControlStatus {
+        match self {
+            ControlResponse::Status { status, .. }
+            | ControlResponse::GetLogInfo { status, .. }
+            | ControlResponse::GetDefaultLogLevel { status, .. }
+            | ControlResponse::GetSoftwareVersion { status, .. }
+            | ControlResponse::GetLogChannelNames { status, .. }
+            | ControlResponse::UnregisterContext { status, .. } => *status,
+        }
+    }
+
+    pub fn to_bytes(&self, big_endian: bool) -> Result<Vec<u8>, DltEncodeError> {
+        let mut w = PayloadWriter::new(big_endian);
+        w.u32(self.service_id());
+        w.bytes(&[self.status().to_u8()]);
+        match self {
+            ControlResponse::Status { .. } => {}
+            ControlResponse::GetLogInfo { status, apps, com_interface } => {
+                if log_info_has_payload(*status) {
+                    write_log_info(&mut w, status.to_u8(), apps)?;
+                }
+                w.bytes(com_interface);
+            }
+            ControlResponse::GetDefaultLogLevel { log_level, .. } => {
+                w.bytes(&[*log_level as u8]);
+            }
+            ControlResponse::GetSoftwareVersion { version, .. } => {
+                w.u32(len_u32(version.len())?);
+                w.bytes(version.as_bytes());
+            }
+            ControlResponse::GetLogChannelNames { names, .. } => {
+                w.bytes(&[len_u8(names.len())?]);
+                for name in names {
+                    w.bytes(name);
+                }
+            }
+            ControlResponse::UnregisterContext { apid, ctid, com_interface, .. } => {
+                w.bytes(&apid.0);
+                w.bytes(&ctid.0);
+                w.bytes(com_interface);
+            }
+        }
+        Ok(w.finish())
+    }
+
+    pub fn from_bytes(payload: &[u8], big_endian: bool) -> Result<Self, DltParseError> {
+        let mut r = PayloadReader::new(payload, big_endian);
+        let service_id = r.u32()?;
+        let status = ControlStatus::from_u8(r.u8()?);
+        let response = match service_id {
+            DLT_SERVICE_ID_GET_LOG_INFO => {
+                let apps = if log_info_has_payload(status) {
+                    read_log_info(&mut r, status.to_u8())?
+                } else {
+                    Vec::new()
+                };
+                ControlResponse::GetLogInfo { status, apps, com_interface: r.id()? }
+            }
+            DLT_SERVICE_ID_GET_DEFAULT_LOG_LEVEL => ControlResponse::GetDefaultLogLevel {
+                status,
+                log_level: r.u8()? as i8,
+            },
+            DLT_SERVICE_ID_GET_SOFTWARE_VERSION => {
+                let len = r.u32()? as usize;
+                let version = String::from_utf8_lossy(r.take(len)?)
+                    .trim_end_matches('\0')
+                    .to_string();
+                ControlResponse::GetSoftwareVersion { status, version }
+            }
+            DLT_SERVICE_ID_GET_LOG_CHANNEL_NAMES => {
+                let count = r.u8()? as usize;
+                let mut names = Vec::with_capacity(count);
+                for _ in 0..count {
+                    names.push(r.id()?);
+                }
+                ControlResponse::GetLogChannelNames { status, names }
+            }
+            DLT_SERVICE_ID_UNREGISTER_CONTEXT => ControlResponse::UnregisterContext {
+                status,
+                apid: AppId(r.id()?),
+                ctid: ContextId(r.id()?),
+                com_interface: r.id()?,
+            },
+            _ => ControlResponse::Status { service_id, status },
+        };
+        Ok(response)
+    }
+}
+
+impl fmt::Display for ControlResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{} {}]", service_name(self.service_id()), self.status())?;
+        match self {
+            ControlResponse::GetLogInfo { apps, .. } => {
+                for app in apps {
+                    write!(f, " {}", app.apid.as_str())?;
+                    for ctx in &app.contexts {
+                        write!(f, " {}", ctx.ctid.as_str())?;
+                        if let Some(level) = ctx.log_level {
+                            write!(f, ":{}", level)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            ControlResponse::GetDefaultLogLevel { log_level, .. } => write!(f, " {}", log_level),
+            ControlResponse::GetSoftwareVersion { version, .. } => write!(f, " {}", version),
+            ControlResponse::GetLogChannelNames { names, .. } => {
+                for name in names {
+                    write!(f, " {}", String::from_utf8_lossy(name).trim_end_matches('\0'))?;
+                }
+                Ok(())
+            }
+            ControlResponse::UnregisterContext { apid, ctid, .. } => {
+                write!(f, " {} {}", apid.as_str(), ctid.as_str())
+            }
+            ControlResponse::Status { .. } => Ok(()),
+        }
+    }
+}
+
+fn log_info_has_payload(status: ControlStatus) -> bool {
+    matches!(status, ControlStatus::Other(DLT_GET_LOG_INFO_IDS..=DLT_GET_LOG_INFO_WITH_DESCRIPTIONS))
+}
+
+fn log_info_has_level(options: u8) -> bool {
+    matches!(options, DLT_GET_LOG_INFO_WITH_LEVEL | DLT_GET_LOG_INFO_WITH_BOTH | DLT_GET_LOG_INFO_WITH_DESCRIPTIONS)
+}
+
+fn log_info_has_trace(options: u8) -> bool {
+    matches!(options, DLT_GET_LOG_INFO_WITH_TRACE | DLT_GET_LOG_INFO_WITH_BOTH | DLT_GET_LOG_INFO_WITH_DESCRIPTIONS)
+}
+
+// Lengths and counts checked against the width of their field
+fn len_u8(len: usize) -> Result<u8, DltEncodeError> {
+    u8::try_from(len).map_err(|_| DltEncodeError::FieldTooLong { len, max: u8::MAX as usize })
+}
+
+fn len_u16(len: usize) -> Result<u16, DltEncodeError> {
+    u16::try_from(len).map_err(|_| DltEncodeError::FieldTooLong { len, max: u16::MAX as usize })
+}
+
+fn len_u32(len: usize) -> Result<u32, DltEncodeError> {
+    u32::try_from(len).map_err(|_| DltEncodeError::FieldTooLong { len, max: u32::MAX as usize })
+}
+
+fn write_log_info(w: &mut PayloadWriter, options: u8, apps: &[AppLogInfo]) -> Result<(), DltEncodeError> {
+    let with_desc = options == DLT_GET_LOG_INFO_WITH_DESCRIPTIONS;
+    w.u16(len_u16(apps.len())?);
+    for app in apps {
+        w.bytes(&app.apid.0);
+        w.u16(len_u16(app.contexts.len())?);
+        for ctx in &app.contexts {
+            w.bytes(&ctx.ctid.0);
+            if log_info_has_level(options) {
+                w.bytes(&[ctx.log_level.unwrap_or(DLT_LOG_LEVEL_DEFAULT) as u8]);
+            }
+            if log_info_has_trace(options) {
+                w.bytes(&[ctx.trace_status.unwrap_or(DLT_LOG_LEVEL_DEFAULT) as u8]);
+            }
+            if with_desc {
+                let desc = ctx.description.as_deref().unwrap_or("");
+                w.u16(len_u16(desc.len())?);
+                w.bytes(desc.as_bytes());
+            }
+        }
+        if with_desc {
+            let desc = app.description.as_deref().unwrap_or("");
+            w.u16(len_u16(desc.len())?);
+            w.bytes(desc.as_bytes());
+        }
+    }
+    Ok(())
+}
+
+fn read_log_info(r: &mut PayloadReader, options: u8) -> Result<Vec<AppLogInfo>, DltParseError> {
+    let with_desc = options == DLT_GET_LOG_INFO_WITH_DESCRIPTIONS;
+    let read_desc = |r: &mut PayloadReader| -> Result<String, DltParseError> {
+        let len = r.u16()? as usize;
+        Ok(String::from_utf8_lossy(r.take(len)?).to_string())
+    };
+
+    let app_count = r.u16()? as usize;
+    let mut apps = Vec::with_capacity(app_count);
+    for _ in 0..app_count {
+        let apid = AppId(r.id()?);
+        let ctx_count = r.u16()? as usize;
+        let mut contexts = Vec::with_capacity(ctx_count);
+        for _ in 0..ctx_count {
+            let ctid = ContextId(r.id()?);
+            let log_level = if log_info_has_level(options) { Some(r.u8()? as i8) } else { None };
+            let trace_status = if log_info_has_trace(options) { Some(r.u8()? as i8) } else { None };
+            let description = if with_desc { Some(read_desc(r)?) } else { None };
+            contexts.push(ContextLogInfo { ctid, log_level, trace_status, description });
+        }
+        let description = if with_desc { Some(read_desc(r)?) } else { None };
+        apps.push(AppLogInfo { apid, contexts, description });
+    }
+    Ok(apps)
+}
@@ -0,0 +1,216 @@
+// Message counter (MCNT) gap detection
+// Senders increment the 8-bit counter per context and let it wrap, so a receiver can
+// detect loss per source (ECU, application, context). Gaps of 256 or more messages
+// are indistinguishable from no gap. A counter at most 128 behind the expected value
+// is taken as a late (reordered or repeated) message. Messages of one context may
+// arrive out of order (several user buffers and connections), so a gap is held back
+// until the source has moved 128 counters past it; the messages that arrived late in
+// the meantime are not reported as lost. flush confirms the gaps still held back.
+use crate::message_ref::DltMessageRef;
+use crate::protocol_v2::DltMessageV2;
+use crate::types::{AppId, ContextId, EcuId, MessageType};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+use core::fmt;
+
+// Counter values a late message may lag behind before it is read as a gap
+const DLT_COUNTER_LATE_WINDOW: u8 = 128;
+
+// Messages missing between two consecutive counters of one source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DltCounterGap {
+    pub ecu: EcuId,
+    pub apid: AppId,
+    pub ctid: ContextId,
+    pub expected: u8,
+    pub received: u8,
+    pub lost: u8,
+}
+
+impl fmt::Display for DltCounterGap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}: expected mcnt {}, got {} ({} lost)",
+            self.ecu.as_str(), self.apid.as_str(), self.ctid.as_str(), self.expected, self.received, self.lost)
+    }
+}
+
+// Counters of one source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DltCounterStats {
+    pub received: u64,
+    pub lost: u64,
+    pub gaps: u64,
+    pub late: u64,
+}
+
+impl fmt::Display for DltCounterStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "received={} lost={} gaps={} late={}", self.received, self.lost, self.gaps, self.late)
+    }
+}
+
+// Counter values skipped by one jump ahead, waiting for late messages
+struct HeldGap {
+    position: u64, // Position of the first skipped value
+    first: u8,
+    len: u8,
+}
+
+struct SourceState {
+    expected: u8,
+    position: u64,          // Counter values passed so far, not wrapped
+    missing: [u64; 4],      // One bit per counter value of a held gap still missing
+    held: VecDeque<HeldGap>, // Oldest first
+    stats: DltCounterStats,
+}
+
+impl SourceState {
+    fn new(mcnt: u8) -> Self {
+        SourceState {
+            expected: mcnt.wrapping_add(1),
+            position: 1,
+            missing: [0; 4],
+            held: VecDeque::new(),
+            stats: DltCounterStats { received: 1, ..Default::default() },
+        }
+    }
+
+    fn set_missing(&mut self, mcnt: u8) {
+        self.missing[mcnt as usize / 64] |= 1 << (mcnt % 64);
+    }
+
+    // Clear the bit; returns whether it was set
+    fn take_missing(&mut self, mcnt: u8) -> bool {
+        let bit = 1 << (mcnt % 64);
+        let was_set = self.missing[mcnt as usize / 64] & bit != 0;
+        self.missing[mcnt as usize / 64] &= !bit;
+        was_set
+    }
+
+    // Report the held gaps for which no late message can come any more (all of them
+    // if `all`), minus the messages that did arrive late
+    fn confirm(&mut self, key: (EcuId, AppId, ContextId), all: bool, gaps: &mut Vec<DltCounterGap>) {
+        while let Some(held) = self.held.front() {
+            let last = held.position + held.len as u64 - 1;
+            if !all && self.position - last <= DLT_COUNTER_LATE_WINDOW as u64 {
+                break;
+            }
+            let HeldGap { first, len, .. } = *held;
+            self.held.pop_front();
+            let lost = (0..len).filter(|i| self.take_missing(first.wrapping_add(*i))).count() as u8;
+            if lost == 0 {
+                continue;
+            }
+            let (ecu, apid, ctid) = key;
+            gaps.push(DltCounterGap { ecu, apid, ctid, expected: first, received: first.wrapping_add(len), lost });
+            self.stats.lost += lost as u64;
+            self.stats.gaps += 1;
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct DltCounterTracker {
+    sources: BTreeMap<(EcuId, AppId, ContextId), SourceState>,
+}
+
+impl DltCounterTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Record the counter of a received message; returns the gaps confirmed by it
+    pub fn observe(&mut self, ecu: EcuId, apid: AppId, ctid: ContextId, mcnt: u8) -> Vec<DltCounterGap> {
+        let key = (ecu, apid, ctid);
+        let mut gaps = Vec::new();
+        let state = match self.sources.get_mut(&key) {
+            Some(state) => state,
+            None => {
+                // First message of a source starts its sequence
+                self.sources.insert(key, SourceState::new(mcnt));
+                return gaps;
+            }
+        };
+        state.stats.received += 1;
+
+        let ahead = mcnt.wrapping_sub(state.expected);
+        if ahead >= DLT_COUNTER_LATE_WINDOW {
+            state.stats.late += 1;
+            state.take_missing(mcnt);
+            return gaps;
+        }
+        if ahead > 0 {
+            state.held.push_back(HeldGap { position: state.position, first: state.expected, len: ahead });
+            for i in 0..ahead {
+                state.set_missing(state.expected.wrapping_add(i));
+            }
+        }
+        state.expected = mcnt.wrapping_add(1);
+        state.position += ahead as u64 + 1;
+        state.confirm(key, false, &mut gaps);
+        gaps
+    }
+
+    // Record a received message, keyed by its header ECU (storage header ECU if absent)
+    // Control messages are not counted: daemons answer with a fixed counter
+    pub fn observe_message(&mut self, msg: &DltMessageRef) -> Vec<DltCounterGap> {
+        let info = msg.message_info();
+        if info.and_then(|info| info.message_type()) == Some(MessageType::Control) {
+            return Vec::new();
+        }
+        let ecu = msg.header_ecu().unwrap_or_else(|| msg.ecu());
+        let apid = msg.apid().unwrap_or(AppId([0; 4]));
+        let ctid = msg.ctid().unwrap_or(ContextId([0; 4]));
+        self.observe(ecu, apid, ctid, msg.mcnt())
+    }
+
+    // Version 2 message, tracked with version 1 sources of the same IDs. Messages
+    // with an ID longer than 4 bytes or otherwise invalid are not tracked.
+    pub fn observe_message_v2(&mut self, msg: &DltMessageV2) -> Vec<DltCounterGap> {
+        if msg.msin.message_type() == Some(MessageType::Control) {
+            return Vec::new();
+        }
+        let ecu = match msg.ecu.as_deref().map(EcuId::try_new).transpose() {
+            Ok(ecu) => ecu.unwrap_or(msg.storage_header.ecu),
+            Err(_) => return Vec::new(),
+        };
+        let (Ok(apid), Ok(ctid)) = (
+            msg.apid.as_deref().map(AppId::try_new).transpose(),
+            msg.ctid.as_deref().map(ContextId::try_new).transpose(),
+        ) else {
+            return Vec::new();
+        };
+        self.observe(ecu, apid.unwrap_or(AppId([0; 4])), ctid.unwrap_or(ContextId([0; 4])), msg.mcnt)
+    }
+
+    // Counters of one source, if it has sent anything
+    pub fn stats(&self, ecu: EcuId, apid: AppId, ctid: ContextId) -> Option<&DltCounterStats> {
+        self.sources.get(&(ecu, apid, ctid)).map(|state| &state.stats)
+    }
+
+    // All sources, sorted by ECU, application and context
+    pub fn sources(&self) -> Vec<((EcuId, AppId, ContextId), DltCounterStats)> {
+        self.sources
+            .iter()
+            .map(|(key, state)| (*key, state.stats))
+            .collect()
+    }
+
+    // Confirm all held gaps now (end of the stream: no late messages can follow)
+    pub fn flush(&mut self) -> Vec<DltCounterGap> {
+        let mut gaps = Vec::new();
+        for (key, state) in self.sources.iter_mut() {
+            state.confirm(*key, true, &mut gaps);
+        }
+        gaps
+    }
+
+    // Messages lost over all sources (gaps still held back not included)
+    pub fn total_lost(&self) -> u64 {
+        self.sources.values().map(|state| state.stats.lost).sum()
+    }
+
+    pub fn reset(&mut self) {
+        self.sources.clear();
+    }
+}
@@ -0,0 +1,249 @@
+// Parse errors reported by all protocol decoders
+use crate::types::DltIdError;
+use core::fmt;
+
+// Offsets are relative to the start of the slice handed to the decoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DltParseError {
+    // Input ends before the structure starting at `offset` is complete
+    Truncated { offset: usize, needed: usize, available: usize },
+    // Storage header does not start with "DLT\x01"
+    BadStoragePattern { offset: usize, found: [u8; 4] },
+    // Serial stream message does not start with "DLS\x01"
+    BadSerialPattern { offset: usize, found: [u8; 4] },
+    // Standard header LEN is smaller than the headers it announces
+    LengthMismatch { offset: usize, declared: usize, minimum: usize },
+    // Standard header VERS field holds a protocol version we cannot decode
+    UnsupportedVersion { offset: usize, version: u8 },
+    // Version 2 header with the reserved content info (CNTI) value
+    UnknownContentInfo { offset: usize, cnti: u8 },
+    // Verbose type info field with no supported data type
+    UnknownArgumentType { offset: usize, type_info: u32 },
+    // Control message with a service ID that has no request layout
+    UnknownServiceId { offset: usize, service_id: u32 },
+    // Version 2 segmentation field with a reserved frame type
+    UnknownSegmentType { offset: usize, frame_type: u8 },
+}
+
+impl DltParseError {
+    pub fn offset(&self) -> usize {
+        match *self {
+            DltParseError::Truncated { offset, .. }
+            | DltParseError::BadStoragePattern { offset, .. }
+            | DltParseError::BadSerialPattern { offset, .. }
+            | DltParseError::LengthMismatch { offset, .. }
+            | DltParseError::UnsupportedVersion { offset, .. }
+            | DltParseError::UnknownContentInfo { offset, .. }
+            | DltParseError::UnknownArgumentType { offset, .. }
+            | DltParseError::UnknownServiceId { offset, .. }
+            | DltParseError::UnknownSegmentType { offset, .. } => offset,
+        }
+    }
+
+    // Same error with its offset moved by `base` (when the input was a sub-slice)
+    pub fn rebase(mut self, base: usize) -> Self {
+        match self {
+            DltParseError::Truncated { ref mut offset, .. }
+            | DltParseError::BadStoragePattern { ref mut offset, .. }
+            | DltParseError::BadSerialPattern { ref mut offset, .. }
+            | DltParseError::LengthMismatch { ref mut offset, .. }
+            | DltParseError::UnsupportedVersion { ref mut offset, .. }
+            | DltParseError::UnknownContentInfo { ref mut offset, .. }
+            | DltParseError::UnknownArgumentType { ref mut offset, .. }
+            | DltParseError::UnknownServiceId { ref mut offset, .. }
+            | DltParseError::UnknownSegmentType { ref mut offset, .. } => *offset += base,
+        }
+        self
+    }
+
+    // Short identifier used for logging and counters
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DltParseError::Truncated { .. } => "truncated",
+            DltParseError::BadStoragePattern { .. } => "bad_storage_pattern",
+            DltParseError::BadSerialPattern { .. } => "bad_serial_pattern",
+            DltParseError::LengthMismatch { .. } => "length_mismatch",
+            DltParseError::UnsupportedVersion { .. } => "unsupported_version",
+            DltParseError::UnknownContentInfo { .. } => "unknown_content_info",
+            DltParseError::UnknownArgumentType { .. } => "unknown_argument_type",
+            DltParseError::UnknownServiceId { .. } => "unknown_service_id",
+            DltParseError::UnknownSegmentType { .. } => "unknown_segment_type",
+        }
+    }
+
+    // Check that `bytes` holds at least `needed` bytes from `offset`
+    pub(crate) fn check_len(bytes: &[u8], offset: usize, needed: usize) -> Result<(), DltParseError> {
+        let available = bytes.len().saturating_sub(offset);
+        if available < needed {
+            return Err(DltParseError::Truncated { offset, needed, available });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DltParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DltParseError::Truncated { offset, needed, available } => {
+                write!(f, "truncated at offset {}: need {} bytes, have {}", offset, needed, available)
+            }
+            DltParseError::BadStoragePattern { offset, found } => {
+                write!(f, "bad storage pattern at offset {}: {:02x?}", offset, found)
+            }
+            DltParseError::BadSerialPattern { offset, found } => {
+                write!(f, "bad serial header at offset {}: {:02x?}", offset, found)
+            }
+            DltParseError::LengthMismatch { offset, declared, minimum } => {
+                write!(f, "length mismatch at offset {}: LEN {} < header size {}", offset, declared, minimum)
+            }
+            DltParseError::UnsupportedVersion { offset, version } => {
+                write!(f, "unsupported protocol version at offset {}: {}", offset, version)
+            }
+            DltParseError::UnknownContentInfo { offset, cnti } => {
+                write!(f, "unknown content info at offset {}: {}", offset, cnti)
+            }
+            DltParseError::UnknownArgumentType { offset, type_info } => {
+                write!(f, "unknown argument type at offset {}: type info 0x{:08x}", offset, type_info)
+            }
+            DltParseError::UnknownServiceId { offset, service_id } => {
+                write!(f, "unknown service id at offset {}: 0x{:x}", offset, service_id)
+            }
+            DltParseError::UnknownSegmentType { offset, frame_type } => {
+                write!(f, "unknown segmentation frame type at offset {}: {}", offset, frame_type)
+            }
+        }
+    }
+}
+
+impl core::error::Error for DltParseError {}
+
+// Encoder errors: a value does not fit its field in the wire format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DltEncodeError {
+    // String, raw data, name or unit longer than its 16-bit length field allows
+    ArgumentTooLong { len: usize, max: usize },
+    // More arguments than the 8-bit NOAR field can count
+    TooManyArguments { count: usize },
+    // Headers plus payload longer than the 16-bit standard header LEN allows
+    MessageTooLong { len: usize },
+    // ECU, application or context ID that does not fit a 4-byte header field
+    InvalidId(DltIdError),
+    // Length or count larger than its field in a control or version 2 message allows
+    FieldTooLong { len: usize, max: usize },
+}
+
+impl fmt::Display for DltEncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DltEncodeError::ArgumentTooLong { len, max } => {
+                write!(f, "argument too long: {} bytes, at most {}", len, max)
+            }
+            DltEncodeError::TooManyArguments { count } => {
+                write!(f, "too many arguments: {}, at most {}", count, u8::MAX)
+            }
+            DltEncodeError::MessageTooLong { len } => {
+                write!(f, "message too long: {} bytes, at most {}", len, u16::MAX)
+            }
+            DltEncodeError::InvalidId(e) => write!(f, "invalid ID: {}", e),
+            DltEncodeError::FieldTooLong { len, max } => {
+                write!(f, "field too long: {}, at most {}", len, max)
+            }
+        }
+    }
+}
+
+impl core::error::Error for DltEncodeError {}
+
+impl From<DltIdError> for DltEncodeError {
+    fn from(e: DltIdError) -> Self {
+        DltEncodeError::InvalidId(e)
+    }
+}
+
+// Message conversion errors: the input does not decode, or the result does not encode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DltConvertError {
+    Parse(DltParseError),
+    Encode(DltEncodeError),
+}
+
+impl From<DltParseError> for DltConvertError {
+    fn from(e: DltParseError) -> Self {
+        DltConvertError::Parse(e)
+    }
+}
+
+impl From<DltEncodeError> for DltConvertError {
+    fn from(e: DltEncodeError) -> Self {
+        DltConvertError::Encode(e)
+    }
+}
+
+impl fmt::Display for DltConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DltConvertError::Parse(e) => write!(f, "{}", e),
+            DltConvertError::Encode(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl core::error::Error for DltConvertError {}
+
+// Per-kind error counters for receivers
+#[derive(Debug, Clone, Default)]
+pub struct DltParseErrorStats {
+    pub truncated: u64,
+    pub bad_storage_pattern: u64,
+    pub bad_serial_pattern: u64,
+    pub length_mismatch: u64,
+    pub unsupported_version: u64,
+    pub unknown_content_info: u64,
+    pub unknown_argument_type: u64,
+    pub unknown_service_id: u64,
+    pub unknown_segment_type: u64,
+}
+
+impl DltParseErrorStats {
+    pub fn record(&mut self, err: &DltParseError) {
+        match err {
+            DltParseError::Truncated { .. } => self.truncated += 1,
+            DltParseError::BadStoragePattern { .. } => self.bad_storage_pattern += 1,
+            DltParseError::BadSerialPattern { .. } => self.bad_serial_pattern += 1,
+            DltParseError::LengthMismatch { .. } => self.length_mismatch += 1,
+            DltParseError::UnsupportedVersion { .. } => self.unsupported_version += 1,
+            DltParseError::UnknownContentInfo { .. } => self.unknown_content_info += 1,
+            DltParseError::UnknownArgumentType { .. } => self.unknown_argument_type += 1,
+            DltParseError::UnknownServiceId { .. } => self.unknown_service_id += 1,
+            DltParseError::UnknownSegmentType { .. } => self.unknown_segment_type += 1,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.truncated
+            + self.bad_storage_pattern
+            + self.bad_serial_pattern
+            + self.length_mismatch
+            + self.unsupported_version
+            + self.unknown_content_info
+            + self.unknown_argument_type
+            + self.unknown_service_id
+            + self.unknown_segment_type
+    }
+}
+
+impl fmt::Display for DltParseErrorStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "truncated={} bad_storage_pattern={} bad_serial_pattern={} length_mismatch={} unsupported_version={} unknown_content_info={} unknown_argument_type={} unknown_service_id={} unknown_segment_type={}",
+            self.truncated,
+            self.bad_storage_pattern,
+            self.bad_serial_pattern,
+            self.length_mismatch,
+            self.unsupported_version,
+            self.unknown_content_info,
+            self.unknown_argument_type,
+            self.unknown_service_id,
+            self.unknown_segment_type,
+        )
+    }
+}
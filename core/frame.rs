@@ -0,0 +1,311 @@
+// Streaming frame decoder: turns arbitrary byte chunks into complete messages
+// After corrupt input it skips ahead to the next plausible message instead of
+// losing the rest of the stream. Protocol versions 1 and 2 are told apart by the
+// VERS bits of the first header byte.
+use crate::clock::DltClock;
+#[cfg(feature = "std")]
+use crate::clock::SystemClock;
+use crate::error::DltParseError;
+use crate::message_ref::DltMessageRef;
+use crate::protocol::{
+    DltStandardHeader, DltStorageHeader, DLT_HTYP_UEH, DLT_HTYP_VERS, DLT_HTYP_WEID,
+    DLT_PROTOCOL_VERSION, DLT_SERIAL_HEADER_PATTERN, DLT_STORAGE_HEADER_PATTERN,
+};
+use crate::protocol_v2::{
+    ContentInfo, DltMessageV2, DLT_HTYP2_CNTI, DLT_PROTOCOL_VERSION_2, DLT_V2_BASE_HEADER_SIZE,
+};
+use crate::types::EcuId;
+use core::fmt;
+use alloc::{boxed::Box, vec::Vec};
+
+const STORAGE_HEADER_SIZE: usize = 16;
+const EXTENDED_HEADER_SIZE: usize = 10;
+
+// Bytes dropped while resynchronizing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DltSkipped {
+    pub offset: u64,           // Stream offset of the first dropped byte
+    pub len: usize,
+    pub reason: DltParseError, // Why the first dropped byte did not start a message
+}
+
+impl fmt::Display for DltSkipped {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "skipped {} bytes at stream offset {} ({})", self.len, self.offset, self.reason)
+    }
+}
+
+// Output of DltFrameDecoder::next_frame
+#[derive(Debug)]
+pub enum DltFrame<'a> {
+    // Complete message; a storage header replaces the serial header or is synthesized
+    Message(DltMessageRef<'a>),
+    // Complete protocol version 2 message, decoded (DltMessageRef is version 1 only)
+    MessageV2(DltMessageV2),
+    Skipped(DltSkipped),
+}
+
+// What precedes each message on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DltStreamFormat {
+    StorageHeader, // "DLT\x01" storage header (this daemon's output, files)
+    Bare,          // Nothing: message starts at the standard header (COVESA TCP)
+    SerialHeader,  // "DLS\x01" serial header (UART)
+}
+
+impl DltStreamFormat {
+    // Bytes before the standard header
+    pub fn prefix_size(&self) -> usize {
+        match self {
+            DltStreamFormat::StorageHeader => STORAGE_HEADER_SIZE,
+            DltStreamFormat::Bare => 0,
+            DltStreamFormat::SerialHeader => DLT_SERIAL_HEADER_PATTERN.len(),
+        }
+    }
+}
+
+pub struct DltFrameDecoder {
+    buffer: Vec<u8>,
+    start: usize,          // First unconsumed byte in buffer
+    stream_offset: u64,    // Stream offset of buffer[start]
+    frame_offset: u64,     // Stream offset of the last frame returned
+    format: DltStreamFormat,
+    ecu: EcuId,            // Storage header ECU when neither stream nor message has one
+    clock: Box<dyn DltClock + Send>, // Receive time of synthesized storage headers
+    frame: Vec<u8>,        // Message with synthesized storage header
+    skip: Option<DltSkipped>, // Skipped range still growing, reported once a message starts
+    skipped_bytes: u64,
+}
+
+// Constructors stamping synthesized storage headers with the system time
+#[cfg(feature = "std")]
+impl DltFrameDecoder {
+    // Stream of messages with storage headers ("DLT\x01", as written by this daemon and in files)
+    pub fn new() -> Self {
+        Self::with_format(DltStreamFormat::StorageHeader, EcuId([0; 4]))
+    }
+
+    // Stream without storage headers; they are stamped with receive time and the
+    // header ECU ID, or `ecu` if the message has none.
+    pub fn with_format(format: DltStreamFormat, ecu: EcuId) -> Self {
+        Self::with_clock(format, ecu, Box::new(SystemClock))
+    }
+
+    // Stream of bare messages starting at the standard header (COVESA TCP)
+    pub fn without_storage_header(ecu: EcuId) -> Self {
+        Self::with_format(DltStreamFormat::Bare, ecu)
+    }
+
+    // Stream of messages with serial headers (DLT over UART)
+    pub fn with_serial_header(ecu: EcuId) -> Self {
+        Self::with_format(DltStreamFormat::SerialHeader, ecu)
+    }
+}
+
+impl DltFrameDecoder {
+    // Like with_format, with receive time from `clock` (the only constructor without
+    // the "std" feature)
+    pub fn with_clock(format: DltStreamFormat, ecu: EcuId, clock: Box<dyn DltClock + Send>) -> Self {
+        DltFrameDecoder {
+            buffer: Vec::new(),
+            start: 0,
+            stream_offset: 0,
+            frame_offset: 0,
+            format,
+            ecu,
+            clock,
+            frame: Vec::new(),
+            skip: None,
+            skipped_bytes: 0,
+        }
+    }
+
+    pub fn format(&self) -> DltStreamFormat {
+        self.format
+    }
+
+    // Append received bytes
+    pub fn push(&mut self, chunk: &[u8]) {
+        if self.start > 0 {
+            self.buffer.drain(..self.start);
+            self.start = 0;
+        }
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    // Next message or skipped range; None until more data is pushed
+    pub fn next_frame(&mut self) -> Option<DltFrame<'_>> {
+        let rest = &self.buffer[self.start..];
+        let len = match self.frame_len(rest) {
+            Ok(len) => len,
+            Err(DltParseError::Truncated { .. }) => return None,
+            Err(reason) => {
+                // Resync: drop bytes up to the next position that could start a message
+                let len = (1..rest.len())
+                    .find(|&i| !matches!(self.frame_len(&rest[i..]), Err(e) if !is_truncated(&e)))
+                    .unwrap_or(rest.len());
+                match &mut self.skip {
+                    Some(skip) => skip.len += len,
+                    None => self.skip = Some(DltSkipped { offset: self.stream_offset, len, reason }),
+                }
+                self.consume(len);
+                self.skipped_bytes += len as u64;
+                return self.next_frame();
+            }
+        };
+        // Report the skipped range before the message that ends it
+        if let Some(skip) = self.skip.take() {
+            return Some(DltFrame::Skipped(skip));
+        }
+
+        let start = self.start;
+        self.frame_offset = self.stream_offset;
+        self.consume(len);
+        let bytes = &self.buffer[start..start + len];
+        let storage_header = self.format == DltStreamFormat::StorageHeader;
+        let header = &bytes[self.format.prefix_size()..];
+        let version = (header[0] & DLT_HTYP_VERS) >> 5;
+        let framed = if storage_header {
+            bytes
+        } else {
+            let ecu = if version == DLT_PROTOCOL_VERSION && header[0] & DLT_HTYP_WEID != 0 {
+                EcuId([header[4], header[5], header[6], header[7]])
+            } else {
+                self.ecu
+            };
+            self.frame.clear();
+            self.frame.extend_from_slice(&DltStorageHeader::with_clock(ecu, &*self.clock).to_bytes());
+            self.frame.extend_from_slice(header);
+            &self.frame[..]
+        };
+
+        if version == DLT_PROTOCOL_VERSION_2 {
+            // Variable-length fields are only checked by the full decode
+            return Some(match DltMessageV2::from_bytes(framed) {
+                Ok(mut msg) => {
+                    // An ECU ID too long for the storage header keeps the stream's
+                    if let (false, Some(Ok(ecu))) = (storage_header, msg.ecu.as_deref().map(EcuId::try_new)) {
+                        msg.storage_header.ecu = ecu;
+                    }
+                    DltFrame::MessageV2(msg)
+                }
+                Err(reason) => {
+                    self.skipped_bytes += len as u64;
+                    DltFrame::Skipped(DltSkipped { offset: self.stream_offset - len as u64, len, reason })
+                }
+            });
+        }
+        // frame_len applied the same checks as DltMessageRef::from_bytes
+        Some(DltFrame::Message(DltMessageRef::from_bytes(framed).expect("validated frame")))
+    }
+
+    // End of stream: drop what is left and report it with any unfinished skipped range
+    pub fn finish(&mut self) -> Option<DltSkipped> {
+        let rest = self.buffered();
+        let reason = self.frame_len(&self.buffer[self.start..]).err();
+        self.consume(rest);
+        self.skipped_bytes += rest as u64;
+        match (self.skip.take(), reason) {
+            (Some(mut skip), _) => {
+                skip.len += rest;
+                Some(skip)
+            }
+            (None, Some(reason)) if rest > 0 => {
+                Some(DltSkipped { offset: self.stream_offset - rest as u64, len: rest, reason })
+            }
+            _ => None,
+        }
+    }
+
+    // Bytes received but not yet emitted (an incomplete message at end of stream)
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.start
+    }
+
+    // Total bytes dropped while resynchronizing
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped_bytes
+    }
+
+    // Bytes consumed from the stream so far
+    pub fn stream_offset(&self) -> u64 {
+        self.stream_offset
+    }
+
+    // Stream offset of the message last returned by next_frame (its storage or serial header)
+    pub fn frame_offset(&self) -> u64 {
+        self.frame_offset
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.start += len;
+        self.stream_offset += len as u64;
+    }
+
+    // Size of the message at the start of `rest`, including its storage/serial header if any
+    fn frame_len(&self, rest: &[u8]) -> Result<usize, DltParseError> {
+        let prefix = self.format.prefix_size();
+        DltParseError::check_len(rest, 0, 4)?;
+        let mut found = [0u8; 4];
+        found.copy_from_slice(&rest[..4]);
+        match self.format {
+            DltStreamFormat::StorageHeader if found != DLT_STORAGE_HEADER_PATTERN => {
+                return Err(DltParseError::BadStoragePattern { offset: 0, found });
+            }
+            DltStreamFormat::SerialHeader if found != DLT_SERIAL_HEADER_PATTERN => {
+                return Err(DltParseError::BadSerialPattern { offset: 0, found });
+            }
+            _ => {}
+        }
+        DltParseError::check_len(rest, 0, prefix)?;
+        let len = check_standard_header(&rest[prefix..]).map_err(|e| e.rebase(prefix))?;
+        DltParseError::check_len(rest, prefix, len)?;
+        Ok(prefix + len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for DltFrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_truncated(err: &DltParseError) -> bool {
+    matches!(err, DltParseError::Truncated { .. })
+}
+
+// Version 2: known content info, LEN covers the base header and its conditional fields
+fn check_base_header_v2(header: &[u8]) -> Result<usize, DltParseError> {
+    DltParseError::check_len(header, 0, DLT_V2_BASE_HEADER_SIZE)?;
+    let cnti = header[0] & DLT_HTYP2_CNTI as u8;
+    let content = ContentInfo::from_u8(cnti).ok_or(DltParseError::UnknownContentInfo { offset: 0, cnti })?;
+    let len = u16::from_be_bytes([header[5], header[6]]) as usize;
+    let minimum = DLT_V2_BASE_HEADER_SIZE + content.fields_size();
+    if len < minimum {
+        return Err(DltParseError::LengthMismatch { offset: 0, declared: len, minimum });
+    }
+    Ok(len)
+}
+
+// Plausibility check of a standard header: known version, LEN covers the announced headers
+fn check_standard_header(header: &[u8]) -> Result<usize, DltParseError> {
+    DltParseError::check_len(header, 0, 4)?;
+    let htyp = header[0];
+    let version = (htyp & DLT_HTYP_VERS) >> 5;
+    if version == DLT_PROTOCOL_VERSION_2 {
+        return check_base_header_v2(header);
+    }
+    if version != DLT_PROTOCOL_VERSION {
+        return Err(DltParseError::UnsupportedVersion { offset: 0, version });
+    }
+    let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+    let mut minimum = DltStandardHeader::size_from_htyp(htyp);
+    if htyp & DLT_HTYP_UEH != 0 {
+        minimum += EXTENDED_HEADER_SIZE;
+    }
+    if len < minimum {
+        return Err(DltParseError::LengthMismatch { offset: 0, declared: len, minimum });
+    }
+    Ok(len)
+}
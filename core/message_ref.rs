@@ -0,0 +1,256 @@
+// Zero-copy message view: borrows from the receive/file buffer, decodes on access
+// Protocol version 1 only; version 2 messages are decoded by DltMessageV2.
+use crate::error::DltParseError;
+use crate::protocol::{
+    DltExtendedHeader, DltMessage, DltStandardHeader, DltStorageHeader, DLT_HTYP_MSBF, DLT_HTYP_UEH,
+    DLT_HTYP_VERS, DLT_HTYP_WEID, DLT_HTYP_WSID, DLT_HTYP_WTMS, DLT_PROTOCOL_VERSION,
+    DLT_STORAGE_HEADER_PATTERN,
+};
+use crate::types::{AppId, ContextId, EcuId, MessageInfo};
+use crate::verbose::DltArgument;
+
+const STORAGE_HEADER_SIZE: usize = 16;
+const EXTENDED_HEADER_SIZE: usize = 10;
+
+// Borrowed DLT message (storage header + standard header + optional extended header + payload)
+#[derive(Debug, Clone, Copy)]
+pub struct DltMessageRef<'a> {
+    bytes: &'a [u8],       // Exactly one message
+    ext_offset: usize,     // Start of extended header (or payload if none)
+    payload_offset: usize,
+}
+
+impl<'a> DltMessageRef<'a> {
+    // Validate framing of the message at the start of `bytes`; trailing data is ignored
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, DltParseError> {
+        DltParseError::check_len(bytes, 0, STORAGE_HEADER_SIZE + 4)?;
+        let mut pattern = [0u8; 4];
+        pattern.copy_from_slice(&bytes[..4]);
+        if pattern != DLT_STORAGE_HEADER_PATTERN {
+            return Err(DltParseError::BadStoragePattern { offset: 0, found: pattern });
+        }
+        let htyp = bytes[STORAGE_HEADER_SIZE];
+        let version = (htyp & DLT_HTYP_VERS) >> 5;
+        if version != DLT_PROTOCOL_VERSION {
+            return Err(DltParseError::UnsupportedVersion { offset: STORAGE_HEADER_SIZE, version });
+        }
+        let len = u16::from_be_bytes([bytes[STORAGE_HEADER_SIZE + 2], bytes[STORAGE_HEADER_SIZE + 3]]) as usize;
+
+        let ext_offset = STORAGE_HEADER_SIZE + DltStandardHeader::size_from_htyp(htyp);
+        let payload_offset = if htyp & DLT_HTYP_UEH != 0 {
+            ext_offset + EXTENDED_HEADER_SIZE
+        } else {
+            ext_offset
+        };
+        let minimum = payload_offset - STORAGE_HEADER_SIZE;
+        if len < minimum {
+            return Err(DltParseError::LengthMismatch { offset: STORAGE_HEADER_SIZE, declared: len, minimum });
+        }
+        DltParseError::check_len(bytes, STORAGE_HEADER_SIZE, len)?;
+        let end = STORAGE_HEADER_SIZE + len;
+
+        Ok(DltMessageRef {
+            bytes: &bytes[..end],
+            ext_offset,
+            payload_offset,
+        })
+    }
+
+    // Raw bytes of this message
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    // Number of bytes this message occupies in the input buffer
+    pub fn size(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn storage_header(&self) -> DltStorageHeader {
+        // Length checked in from_bytes
+        DltStorageHeader::from_bytes(&self.bytes[..STORAGE_HEADER_SIZE]).unwrap()
+    }
+
+    pub fn standard_header(&self) -> DltStandardHeader {
+        DltStandardHeader::from_bytes(&self.bytes[STORAGE_HEADER_SIZE..self.ext_offset]).unwrap()
+    }
+
+    pub fn extended_header(&self) -> Option<DltExtendedHeader> {
+        if !self.has_extended_header() {
+            return None;
+        }
+        DltExtendedHeader::from_bytes(&self.bytes[self.ext_offset..self.payload_offset]).ok()
+    }
+
+    pub fn payload(&self) -> &'a [u8] {
+        &self.bytes[self.payload_offset..]
+    }
+
+    fn htyp(&self) -> u8 {
+        self.bytes[STORAGE_HEADER_SIZE]
+    }
+
+    pub fn has_extended_header(&self) -> bool {
+        self.htyp() & DLT_HTYP_UEH != 0
+    }
+
+    pub fn is_big_endian(&self) -> bool {
+        self.htyp() & DLT_HTYP_MSBF != 0
+    }
+
+    pub fn mcnt(&self) -> u8 {
+        self.bytes[STORAGE_HEADER_SIZE + 1]
+    }
+
+    // ECU ID from the storage header
+    pub fn ecu(&self) -> EcuId {
+        let mut ecu = [0u8; 4];
+        ecu.copy_from_slice(&self.bytes[12..16]);
+        EcuId(ecu)
+    }
+
+    // Optional standard header fields, read in place
+    fn optional_field(&self, flag: u8) -> Option<[u8; 4]> {
+        let htyp = self.htyp();
+        if htyp & flag == 0 {
+            return None;
+        }
+        let mut offset = STORAGE_HEADER_SIZE + 4;
+        for f in [DLT_HTYP_WEID, DLT_HTYP_WSID] {
+            if f < flag && htyp & f != 0 {
+                offset += 4;
+            }
+        }
+        let mut field = [0u8; 4];
+        field.copy_from_slice(&self.bytes[offset..offset + 4]);
+        Some(field)
+    }
+
+    pub fn header_ecu(&self) -> Option<EcuId> {
+        self.optional_field(DLT_HTYP_WEID).map(EcuId)
+    }
+
+    pub fn session_id(&self) -> Option<u32> {
+        self.optional_field(DLT_HTYP_WSID).map(u32::from_be_bytes)
+    }
+
+    pub fn timestamp(&self) -> Option<u32> {
+        self.optional_field(DLT_HTYP_WTMS).map(u32::from_be_bytes)
+    }
+
+    pub fn message_info(&self) -> Option<MessageInfo> {
+        self.has_extended_header()
+            .then(|| MessageInfo::from_byte(self.bytes[self.ext_offset]))
+    }
+
+    pub fn noar(&self) -> u8 {
+        if self.has_extended_header() { self.bytes[self.ext_offset + 1] } else { 0 }
+    }
+
+    pub fn apid(&self) -> Option<AppId> {
+        self.extended_header().map(|ext| ext.apid)
+    }
+
+    pub fn ctid(&self) -> Option<ContextId> {
+        self.extended_header().map(|ext| ext.ctid)
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.message_info().is_some_and(|info| info.verbose)
+    }
+
+    // Lazily decode verbose arguments one at a time
+    pub fn arguments(&self) -> ArgumentIter<'a> {
+        let remaining = if self.is_verbose() { self.noar() } else { 0 };
+        ArgumentIter {
+            payload: self.payload(),
+            payload_offset: self.payload_offset,
+            offset: 0,
+            remaining,
+            big_endian: self.is_big_endian(),
+        }
+    }
+
+    // Copy into an owned message
+    pub fn to_message(&self) -> DltMessage {
+        DltMessage {
+            storage_header: self.storage_header(),
+            standard_header: self.standard_header(),
+            extended_header: self.extended_header(),
+            payload: self.payload().to_vec(),
+        }
+    }
+}
+
+// Iterator over the verbose arguments of a borrowed message
+// Error offsets are relative to the message start; iteration ends after an error
+pub struct ArgumentIter<'a> {
+    payload: &'a [u8],
+    payload_offset: usize,
+    offset: usize,
+    remaining: u8,
+    big_endian: bool,
+}
+
+impl Iterator for ArgumentIter<'_> {
+    type Item = Result<DltArgument, DltParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match DltArgument::from_bytes(&self.payload[self.offset..], self.big_endian) {
+            Ok((arg, used)) => {
+                self.offset += used;
+                self.remaining -= 1;
+                Some(Ok(arg))
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(e.rebase(self.payload_offset + self.offset)))
+            }
+        }
+    }
+}
+
+// Iterator over back-to-back messages in a buffer
+// Malformed messages are reported and skipped; iteration stops at an incomplete tail
+pub struct DltMessageIter<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> DltMessageIter<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        DltMessageIter { bytes, offset: 0 }
+    }
+
+    // Bytes consumed so far (start of the first unparsed message)
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for DltMessageIter<'a> {
+    type Item = Result<DltMessageRef<'a>, DltParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.bytes[self.offset..];
+        match DltMessageRef::from_bytes(rest) {
+            Ok(msg) => {
+                self.offset += msg.size();
+                Some(Ok(msg))
+            }
+            // Wait for more data
+            Err(DltParseError::Truncated { .. }) => None,
+            Err(e) => {
+                // Skip by LEN if the message is complete, otherwise drop the rest of the buffer
+                let len = u16::from_be_bytes([rest[STORAGE_HEADER_SIZE + 2], rest[STORAGE_HEADER_SIZE + 3]]) as usize;
+                let skip = (STORAGE_HEADER_SIZE + len).min(rest.len());
+                let err = e.rebase(self.offset);
+                self.offset += skip;
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -1,6 +1,31 @@
 // Core module: Protocol definitions and types (NO I/O dependencies)
+// Builds as no_std + alloc with default features off (microcontroller producers)
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod types;
+pub mod clock;
 pub mod protocol;
+pub mod protocol_v2;
+pub mod verbose;
+pub mod nonverbose;
+pub mod control;
+pub mod message_ref;
+pub mod error;
+pub mod frame;
+pub mod network_trace;
+pub mod counter;
 
 pub use types::*;
+pub use clock::*;
 pub use protocol::*;
+pub use protocol_v2::*;
+pub use verbose::*;
+pub use nonverbose::*;
+pub use control::*;
+pub use message_ref::*;
+pub use error::*;
+pub use frame::*;
+pub use network_trace::*;
+pub use counter::*;
@@ -0,0 +1,108 @@
+// Network trace payloads (MSTP = NwTrace, MTIN = bus type)
+// Same argument layout as the COVESA library:
+//   single message:  raw header, raw payload
+//   segmented:       "NWST" id, raw header, total length, segment count, segment size
+//                    "NWCH" id, sequence, raw segment data    (one per segment)
+//                    "NWEN" id
+use crate::verbose::{DltArgument, DltValue};
+use alloc::{string::ToString, vec, vec::Vec};
+
+pub const DLT_TRACE_NW_START: &str = "NWST";
+pub const DLT_TRACE_NW_SEGMENT: &str = "NWCH";
+pub const DLT_TRACE_NW_END: &str = "NWEN";
+
+// Segment size used by the COVESA library (DLT_MAX_TRACE_SEGMENT_SIZE)
+pub const DLT_MAX_TRACE_SEGMENT_SIZE: usize = 1024;
+
+// Largest header + payload that fits one message: LEN is 16 bit and also covers the
+// standard header (up to 16 bytes), extended header (10) and two raw argument headers (12)
+pub const DLT_MAX_TRACE_SINGLE_SIZE: usize = u16::MAX as usize - 16 - 10 - 12;
+
+// Payload of one network trace message
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkTrace {
+    Frame { header: Vec<u8>, payload: Vec<u8> },
+    Start { id: u32, header: Vec<u8>, payload_len: u32, segment_count: u16, segment_size: u16 },
+    Segment { id: u32, sequence: u16, data: Vec<u8> },
+    End { id: u32 },
+}
+
+impl NetworkTrace {
+    // Frames with header + payload above DLT_MAX_TRACE_SINGLE_SIZE must be segmented
+    pub fn fits_single_message(header: &[u8], payload: &[u8]) -> bool {
+        header.len() + payload.len() <= DLT_MAX_TRACE_SINGLE_SIZE
+    }
+
+    // Split one frame into the start/segment.../end sequence
+    // None if the payload needs more than u16::MAX segments
+    pub fn segmented(id: u32, header: &[u8], payload: &[u8], segment_size: usize) -> Option<Vec<NetworkTrace>> {
+        let segment_size = segment_size.clamp(1, u16::MAX as usize);
+        let segment_count = u16::try_from(payload.len().div_ceil(segment_size)).ok()?;
+        let payload_len = u32::try_from(payload.len()).ok()?;
+
+        let mut sequence = Vec::with_capacity(segment_count as usize + 2);
+        sequence.push(NetworkTrace::Start {
+            id,
+            header: header.to_vec(),
+            payload_len,
+            segment_count,
+            segment_size: segment_size as u16,
+        });
+        for (i, data) in payload.chunks(segment_size).enumerate() {
+            sequence.push(NetworkTrace::Segment { id, sequence: i as u16, data: data.to_vec() });
+        }
+        sequence.push(NetworkTrace::End { id });
+        Some(sequence)
+    }
+
+    pub fn to_arguments(&self) -> Vec<DltArgument> {
+        let raw = |data: &[u8]| DltArgument::new(DltValue::Raw(data.to_vec()));
+        let tag = |tag: &str| DltArgument::new(DltValue::String(tag.to_string()));
+        match self {
+            NetworkTrace::Frame { header, payload } => vec![raw(header), raw(payload)],
+            NetworkTrace::Start { id, header, payload_len, segment_count, segment_size } => vec![
+                tag(DLT_TRACE_NW_START),
+                DltArgument::new(DltValue::UInt32(*id)),
+                raw(header),
+                DltArgument::new(DltValue::UInt32(*payload_len)),
+                DltArgument::new(DltValue::UInt16(*segment_count)),
+                DltArgument::new(DltValue::UInt16(*segment_size)),
+            ],
+            NetworkTrace::Segment { id, sequence, data } => vec![
+                tag(DLT_TRACE_NW_SEGMENT),
+                DltArgument::new(DltValue::UInt32(*id)),
+                DltArgument::new(DltValue::UInt16(*sequence)),
+                raw(data),
+            ],
+            NetworkTrace::End { id } => vec![tag(DLT_TRACE_NW_END), DltArgument::new(DltValue::UInt32(*id))],
+        }
+    }
+
+    // None if the arguments do not follow one of the layouts above
+    pub fn from_arguments(args: &[DltArgument]) -> Option<Self> {
+        let values: Vec<&DltValue> = args.iter().map(|arg| &arg.value).collect();
+        let trace = match values.as_slice() {
+            [DltValue::Raw(header), DltValue::Raw(payload)] => {
+                NetworkTrace::Frame { header: header.clone(), payload: payload.clone() }
+            }
+            [DltValue::String(tag), DltValue::UInt32(id), DltValue::Raw(header), DltValue::UInt32(payload_len),
+             DltValue::UInt16(segment_count), DltValue::UInt16(segment_size)] if tag == DLT_TRACE_NW_START => {
+                NetworkTrace::Start {
+                    id: *id,
+                    header: header.clone(),
+                    payload_len: *payload_len,
+                    segment_count: *segment_count,
+                    segment_size: *segment_size,
+                }
+            }
+            [DltValue::String(tag), DltValue::UInt32(id), DltValue::UInt16(sequence), DltValue::Raw(data)]
+                if tag == DLT_TRACE_NW_SEGMENT =>
+            {
+                NetworkTrace::Segment { id: *id, sequence: *sequence, data: data.clone() }
+            }
+            [DltValue::String(tag), DltValue::UInt32(id)] if tag == DLT_TRACE_NW_END => NetworkTrace::End { id: *id },
+            _ => return None,
+        };
+        Some(trace)
+    }
+}
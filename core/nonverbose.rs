@@ -0,0 +1,36 @@
+// Non-verbose mode payload: 32-bit message ID followed by packed arguments
+// Argument types, names and static text are not transmitted; they come from a
+// catalog (FIBEX or equivalent) keyed by message ID.
+use crate::error::{DltEncodeError, DltParseError};
+use crate::verbose::{DltArgument, PayloadReader, PayloadWriter};
+use alloc::vec::Vec;
+
+// Build a non-verbose payload from a message ID and its argument values
+pub fn encode_non_verbose(message_id: u32, args: &[DltArgument], big_endian: bool) -> Result<Vec<u8>, DltEncodeError> {
+    let mut w = PayloadWriter::new(big_endian);
+    w.u32(message_id);
+    for arg in args {
+        w.bytes(&arg.to_packed_bytes(big_endian)?);
+    }
+    Ok(w.finish())
+}
+
+// Split a non-verbose payload into message ID and packed argument data
+pub fn decode_non_verbose(payload: &[u8], big_endian: bool) -> Result<(u32, &[u8]), DltParseError> {
+    let mut r = PayloadReader::new(payload, big_endian);
+    let message_id = r.u32()?;
+    Ok((message_id, &payload[r.pos..]))
+}
+
+// Decode packed arguments using type info fields supplied by a catalog
+pub fn decode_packed_arguments(data: &[u8], type_infos: &[u32], big_endian: bool) -> Result<Vec<DltArgument>, DltParseError> {
+    let mut args = Vec::with_capacity(type_infos.len());
+    let mut offset = 0;
+    for &type_info in type_infos {
+        let (arg, used) = DltArgument::from_bytes_with_type_info(&data[offset..], type_info, big_endian)
+            .map_err(|e| e.rebase(offset))?;
+        args.push(arg);
+        offset += used;
+    }
+    Ok(args)
+}
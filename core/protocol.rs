@@ -1,6 +1,21 @@
 // DLT protocol implementation - pure data structures, no I/O
-use crate::types::{AppId, ContextId, EcuId};
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::types::{AppId, ContextId, ControlType, EcuId, LogLevel, MessageInfo, MessageKind, NetworkTraceType};
+use crate::control::{ControlRequest, ControlResponse};
+use crate::error::{DltEncodeError, DltParseError};
+use crate::message_ref::DltMessageRef;
+use crate::network_trace::NetworkTrace;
+use crate::verbose::{argument_count, decode_arguments, encode_arguments, DltArgument, DltValue};
+use crate::nonverbose::{decode_non_verbose, encode_non_verbose};
+use crate::clock::DltClock;
+#[cfg(feature = "std")]
+use crate::clock::SystemClock;
+use alloc::{string::{String, ToString}, vec::Vec};
+
+// Pattern at the start of every storage header
+pub const DLT_STORAGE_HEADER_PATTERN: [u8; 4] = *b"DLT\x01";
+
+// Serial header sent before each message on UART links (no storage header there)
+pub const DLT_SERIAL_HEADER_PATTERN: [u8; 4] = *b"DLS\x01";
 
 // DLT Storage Header (16 bytes)
 #[repr(C)]
@@ -13,12 +28,18 @@ pub struct DltStorageHeader {
 }
 
 impl DltStorageHeader {
+    // Stamped with the system time; without the "std" feature use with_clock
+    #[cfg(feature = "std")]
     pub fn new(ecu: EcuId) -> Self {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        Self::with_clock(ecu, &SystemClock)
+    }
+
+    pub fn with_clock(ecu: EcuId, clock: &dyn DltClock) -> Self {
+        let (seconds, microseconds) = clock.now();
         DltStorageHeader {
-            pattern: *b"DLT\x01",
-            seconds: now.as_secs() as u32,
-            microseconds: now.subsec_micros(),
+            pattern: DLT_STORAGE_HEADER_PATTERN,
+            seconds,
+            microseconds,
             ecu,
         }
     }
@@ -32,16 +53,17 @@ impl DltStorageHeader {
         bytes
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() < 16 {
-            return None;
-        }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DltParseError> {
+        DltParseError::check_len(bytes, 0, 16)?;
         let mut pattern = [0u8; 4];
         let mut ecu_bytes = [0u8; 4];
         pattern.copy_from_slice(&bytes[0..4]);
         ecu_bytes.copy_from_slice(&bytes[12..16]);
+        if pattern != DLT_STORAGE_HEADER_PATTERN {
+            return Err(DltParseError::BadStoragePattern { offset: 0, found: pattern });
+        }
 
-        Some(DltStorageHeader {
+        Ok(DltStorageHeader {
             pattern,
             seconds: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
             microseconds: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
@@ -50,37 +72,150 @@ impl DltStorageHeader {
     }
 }
 
-// DLT Standard Header (4 bytes minimum)
-#[repr(C)]
+// Standard header type (HTYP) bits
+pub const DLT_HTYP_UEH: u8 = 0x01;  // Use extended header
+pub const DLT_HTYP_MSBF: u8 = 0x02; // Most significant byte first (payload)
+pub const DLT_HTYP_WEID: u8 = 0x04; // With ECU ID
+pub const DLT_HTYP_WSID: u8 = 0x08; // With session ID
+pub const DLT_HTYP_WTMS: u8 = 0x10; // With timestamp
+pub const DLT_HTYP_VERS: u8 = 0xe0; // Protocol version (bits 5-7)
+
+pub const DLT_PROTOCOL_VERSION: u8 = 1;
+
+// Standard header with ECU ID, session ID and timestamp
+pub const DLT_STANDARD_HEADER_MAX_SIZE: usize = 16;
+
+// Standard header timestamp (WTMS) ticks per second (0.1 ms resolution)
+pub const DLT_TIMESTAMP_RESOLUTION: u32 = 10000;
+
+// DLT Standard Header (4 bytes minimum, up to 16 with optional fields)
+// LEN, SEID and TMSP are always big endian; MSBF only applies to the payload.
 #[derive(Debug, Clone)]
 pub struct DltStandardHeader {
-    pub htyp: u8,              // Header type
-    pub mcnt: u8,              // Message counter
-    pub len: u16,              // Length (excluding storage header)
+    pub use_extended_header: bool,
+    pub big_endian: bool,         // MSBF
+    pub version: u8,              // VERS (3 bits)
+    pub mcnt: u8,                 // Message counter
+    pub len: u16,                 // Length (excluding storage header)
+    pub ecu: Option<EcuId>,       // WEID
+    pub session_id: Option<u32>,  // WSID
+    pub timestamp: Option<u32>,   // WTMS, 0.1 ms units
 }
 
 impl DltStandardHeader {
     pub fn new(has_extended: bool, mcnt: u8, len: u16) -> Self {
-        let htyp = if has_extended { 0x35 } else { 0x21 };
-        DltStandardHeader { htyp, mcnt, len }
+        DltStandardHeader {
+            use_extended_header: has_extended,
+            big_endian: false,
+            version: DLT_PROTOCOL_VERSION,
+            mcnt,
+            len,
+            ecu: None,
+            session_id: None,
+            timestamp: None,
+        }
+    }
+
+    pub fn with_ecu_id(mut self, ecu: EcuId) -> Self {
+        self.ecu = Some(ecu);
+        self
+    }
+
+    pub fn with_session_id(mut self, session_id: u32) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u32) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn with_big_endian(mut self, big_endian: bool) -> Self {
+        self.big_endian = big_endian;
+        self
+    }
+
+    pub fn htyp(&self) -> u8 {
+        let mut htyp = (self.version << 5) & DLT_HTYP_VERS;
+        if self.use_extended_header {
+            htyp |= DLT_HTYP_UEH;
+        }
+        if self.big_endian {
+            htyp |= DLT_HTYP_MSBF;
+        }
+        if self.ecu.is_some() {
+            htyp |= DLT_HTYP_WEID;
+        }
+        if self.session_id.is_some() {
+            htyp |= DLT_HTYP_WSID;
+        }
+        if self.timestamp.is_some() {
+            htyp |= DLT_HTYP_WTMS;
+        }
+        htyp
+    }
+
+    // Encoded size including optional fields
+    pub fn size(&self) -> usize {
+        Self::size_from_htyp(self.htyp())
+    }
+
+    pub fn size_from_htyp(htyp: u8) -> usize {
+        let mut size = 4;
+        if htyp & DLT_HTYP_WEID != 0 {
+            size += 4;
+        }
+        if htyp & DLT_HTYP_WSID != 0 {
+            size += 4;
+        }
+        if htyp & DLT_HTYP_WTMS != 0 {
+            size += 4;
+        }
+        size
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(4);
-        bytes.push(self.htyp);
+        let mut bytes = Vec::with_capacity(self.size());
+        bytes.push(self.htyp());
         bytes.push(self.mcnt);
-        bytes.extend_from_slice(&self.len.to_le_bytes());
+        bytes.extend_from_slice(&self.len.to_be_bytes());
+        if let Some(ref ecu) = self.ecu {
+            bytes.extend_from_slice(&ecu.0);
+        }
+        if let Some(session_id) = self.session_id {
+            bytes.extend_from_slice(&session_id.to_be_bytes());
+        }
+        if let Some(timestamp) = self.timestamp {
+            bytes.extend_from_slice(&timestamp.to_be_bytes());
+        }
         bytes
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() < 4 {
-            return None;
-        }
-        Some(DltStandardHeader {
-            htyp: bytes[0],
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DltParseError> {
+        DltParseError::check_len(bytes, 0, 4)?;
+        let htyp = bytes[0];
+        DltParseError::check_len(bytes, 0, Self::size_from_htyp(htyp))?;
+
+        let mut offset = 4;
+        let mut next_field = || {
+            let field = [bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]];
+            offset += 4;
+            field
+        };
+        let ecu = (htyp & DLT_HTYP_WEID != 0).then(|| EcuId(next_field()));
+        let session_id = (htyp & DLT_HTYP_WSID != 0).then(|| u32::from_be_bytes(next_field()));
+        let timestamp = (htyp & DLT_HTYP_WTMS != 0).then(|| u32::from_be_bytes(next_field()));
+
+        Ok(DltStandardHeader {
+            use_extended_header: htyp & DLT_HTYP_UEH != 0,
+            big_endian: htyp & DLT_HTYP_MSBF != 0,
+            version: (htyp & DLT_HTYP_VERS) >> 5,
             mcnt: bytes[1],
-            len: u16::from_le_bytes([bytes[2], bytes[3]]),
+            len: u16::from_be_bytes([bytes[2], bytes[3]]),
+            ecu,
+            session_id,
+            timestamp,
         })
     }
 }
@@ -89,7 +224,7 @@ impl DltStandardHeader {
 #[repr(C)]
 #[derive(Debug, Clone)]
 pub struct DltExtendedHeader {
-    pub msin: u8,              // Message info
+    pub msin: MessageInfo,     // Message info
     pub noar: u8,              // Number of arguments
     pub apid: AppId,
     pub ctid: ContextId,
@@ -98,33 +233,36 @@ pub struct DltExtendedHeader {
 impl DltExtendedHeader {
     pub fn new(apid: AppId, ctid: ContextId, noar: u8) -> Self {
         DltExtendedHeader {
-            msin: 0x01, // Verbose, Log, Info
+            msin: MessageInfo::default(), // Verbose, Log, Info
             noar,
             apid,
             ctid,
         }
     }
 
+    pub fn with_message_info(mut self, msin: MessageInfo) -> Self {
+        self.msin = msin;
+        self
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(10);
-        bytes.push(self.msin);
+        bytes.push(self.msin.to_byte());
         bytes.push(self.noar);
         bytes.extend_from_slice(&self.apid.0);
         bytes.extend_from_slice(&self.ctid.0);
         bytes
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() < 10 {
-            return None;
-        }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DltParseError> {
+        DltParseError::check_len(bytes, 0, 10)?;
         let mut apid = [0u8; 4];
         let mut ctid = [0u8; 4];
         apid.copy_from_slice(&bytes[2..6]);
         ctid.copy_from_slice(&bytes[6..10]);
 
-        Some(DltExtendedHeader {
-            msin: bytes[0],
+        Ok(DltExtendedHeader {
+            msin: MessageInfo::from_byte(bytes[0]),
             noar: bytes[1],
             apid: AppId(apid),
             ctid: ContextId(ctid),
@@ -141,74 +279,299 @@ pub struct DltMessage {
     pub payload: Vec<u8>,
 }
 
+// Constructors stamped with the system time
+#[cfg(feature = "std")]
 impl DltMessage {
-    pub fn new_verbose(ecu: EcuId, apid: AppId, ctid: ContextId, message: &str) -> Self {
-        // Create verbose payload: type info + string length + string
-        let mut payload = Vec::new();
-        payload.extend_from_slice(&[0x00, 0x00, 0x00, 0x21]); // String type
-        let str_len = (message.len() + 1) as u16;
-        payload.extend_from_slice(&str_len.to_le_bytes());
-        payload.extend_from_slice(message.as_bytes());
-        payload.push(0); // null terminator
-
-        let extended_header = Some(DltExtendedHeader::new(apid, ctid, 1));
-        let total_len = 4 + 10 + payload.len(); // std + ext + payload
-
-        DltMessage {
-            storage_header: DltStorageHeader::new(ecu),
-            standard_header: DltStandardHeader::new(true, 0, total_len as u16),
-            extended_header,
+    pub fn new_verbose(ecu: EcuId, apid: AppId, ctid: ContextId, message: &str) -> Result<Self, DltEncodeError> {
+        Self::new_verbose_with_clock(ecu, apid, ctid, message, &SystemClock)
+    }
+
+    pub fn new_verbose_args(ecu: EcuId, apid: AppId, ctid: ContextId, args: &[DltArgument]) -> Result<Self, DltEncodeError> {
+        Self::new_verbose_args_with_clock(ecu, apid, ctid, args, &SystemClock)
+    }
+
+    pub fn new_non_verbose(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        message_id: u32,
+        args: &[DltArgument],
+    ) -> Result<Self, DltEncodeError> {
+        Self::new_non_verbose_with_clock(ecu, apid, ctid, message_id, args, &SystemClock)
+    }
+
+    pub fn new_control_request(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        request: &ControlRequest,
+    ) -> Result<Self, DltEncodeError> {
+        Self::new_control_request_with_clock(ecu, apid, ctid, request, &SystemClock)
+    }
+
+    pub fn new_control_response(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        response: &ControlResponse,
+    ) -> Result<Self, DltEncodeError> {
+        Self::new_control_response_with_clock(ecu, apid, ctid, response, &SystemClock)
+    }
+
+    pub fn new_network_trace(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        trace_type: NetworkTraceType,
+        trace: &NetworkTrace,
+    ) -> Result<Self, DltEncodeError> {
+        Self::new_network_trace_with_clock(ecu, apid, ctid, trace_type, trace, &SystemClock)
+    }
+}
+
+// Constructors stamping the storage header from a caller-supplied clock (the
+// only ones without the "std" feature)
+impl DltMessage {
+    pub fn new_verbose_with_clock(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        message: &str,
+        clock: &dyn DltClock,
+    ) -> Result<Self, DltEncodeError> {
+        let arg = DltArgument::new(DltValue::String(message.to_string()));
+        Self::new_verbose_args_with_clock(ecu, apid, ctid, &[arg], clock)
+    }
+
+    pub fn new_verbose_args_with_clock(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        args: &[DltArgument],
+        clock: &dyn DltClock,
+    ) -> Result<Self, DltEncodeError> {
+        let payload = encode_arguments(args, false)?;
+        let extended_header = DltExtendedHeader::new(apid, ctid, argument_count(args)?);
+        Self::assemble(DltStorageHeader::with_clock(ecu, clock), extended_header, payload)
+    }
+
+    // Non-verbose message: payload is the message ID plus packed argument values
+    pub fn new_non_verbose_with_clock(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        message_id: u32,
+        args: &[DltArgument],
+        clock: &dyn DltClock,
+    ) -> Result<Self, DltEncodeError> {
+        let payload = encode_non_verbose(message_id, args, false)?;
+        let mut extended_header = DltExtendedHeader::new(apid, ctid, argument_count(args)?);
+        extended_header.msin.verbose = false;
+        Self::assemble(DltStorageHeader::with_clock(ecu, clock), extended_header, payload)
+    }
+
+    pub fn new_control_request_with_clock(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        request: &ControlRequest,
+        clock: &dyn DltClock,
+    ) -> Result<Self, DltEncodeError> {
+        let info = MessageInfo::new(false, MessageKind::Control(ControlType::Request));
+        let extended_header = DltExtendedHeader::new(apid, ctid, 1).with_message_info(info);
+        Self::assemble(DltStorageHeader::with_clock(ecu, clock), extended_header, request.to_bytes(false)?)
+    }
+
+    pub fn new_control_response_with_clock(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        response: &ControlResponse,
+        clock: &dyn DltClock,
+    ) -> Result<Self, DltEncodeError> {
+        let info = MessageInfo::new(false, MessageKind::Control(ControlType::Response));
+        let extended_header = DltExtendedHeader::new(apid, ctid, 1).with_message_info(info);
+        Self::assemble(DltStorageHeader::with_clock(ecu, clock), extended_header, response.to_bytes(false)?)
+    }
+
+    // Verbose network trace message (one frame, or one part of a segmented sequence)
+    pub fn new_network_trace_with_clock(
+        ecu: EcuId,
+        apid: AppId,
+        ctid: ContextId,
+        trace_type: NetworkTraceType,
+        trace: &NetworkTrace,
+        clock: &dyn DltClock,
+    ) -> Result<Self, DltEncodeError> {
+        let args = trace.to_arguments();
+        let info = MessageInfo::new(true, MessageKind::NwTrace(trace_type));
+        let extended_header = DltExtendedHeader::new(apid, ctid, argument_count(&args)?).with_message_info(info);
+        Self::assemble(DltStorageHeader::with_clock(ecu, clock), extended_header, encode_arguments(&args, false)?)
+    }
+
+    // Fails unless the payload fits with the largest standard header, so that
+    // with_timestamp cannot push LEN past its limit later
+    fn assemble(
+        storage_header: DltStorageHeader,
+        extended_header: DltExtendedHeader,
+        payload: Vec<u8>,
+    ) -> Result<Self, DltEncodeError> {
+        let len = DLT_STANDARD_HEADER_MAX_SIZE + 10 + payload.len();
+        if len > u16::MAX as usize {
+            return Err(DltEncodeError::MessageTooLong { len });
+        }
+        let ecu = storage_header.ecu;
+        let mut msg = DltMessage {
+            storage_header,
+            standard_header: DltStandardHeader::new(true, 0, 0).with_ecu_id(ecu),
+            extended_header: Some(extended_header),
             payload,
+        };
+        msg.update_length()?;
+        Ok(msg)
+    }
+
+    // Restamp the storage header time from a caller-supplied clock
+    pub fn with_storage_time(mut self, clock: &dyn DltClock) -> Self {
+        (self.storage_header.seconds, self.storage_header.microseconds) = clock.now();
+        self
+    }
+
+    // Stamp the standard header timestamp (WTMS, 0.1 ms ticks since an arbitrary start).
+    // The constructors leave room for it; fails only if the payload was enlarged
+    // past the LEN limit afterwards.
+    pub fn with_timestamp(mut self, timestamp: u32) -> Result<Self, DltEncodeError> {
+        self.standard_header.timestamp = Some(timestamp);
+        self.update_length()?;
+        Ok(self)
+    }
+
+    // Stamp the log level into MSIN, keeping the verbose flag. Only log messages
+    // have a level; other message types are returned unchanged.
+    pub fn with_log_level(mut self, level: LogLevel) -> Self {
+        if let Some(ref mut ext) = self.extended_header {
+            if let MessageKind::Log(ref mut msg_level) = ext.msin.kind {
+                *msg_level = level;
+            }
         }
+        self
+    }
+
+    pub fn message_info(&self) -> Option<MessageInfo> {
+        self.extended_header.as_ref().map(|ext| ext.msin)
+    }
+
+    // Recompute the standard header LEN field after headers or payload changed;
+    // fails (leaving the message unchanged) if the total does not fit LEN
+    pub fn update_length(&mut self) -> Result<(), DltEncodeError> {
+        let ext_len = if self.extended_header.is_some() { 10 } else { 0 };
+        let total_len = self.standard_header.size() + ext_len + self.payload.len();
+        self.standard_header.len =
+            u16::try_from(total_len).map_err(|_| DltEncodeError::MessageTooLong { len: total_len })?;
+        self.standard_header.use_extended_header = self.extended_header.is_some();
+        Ok(())
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.extend_from_slice(&self.storage_header.to_bytes());
+        self.write_message(&mut bytes);
+        bytes
+    }
+
+    // Serial link framing: "DLS\x01" instead of the storage header
+    pub fn to_serial_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&DLT_SERIAL_HEADER_PATTERN);
+        self.write_message(&mut bytes);
+        bytes
+    }
+
+    // Standard header onwards (the part covered by LEN)
+    fn write_message(&self, bytes: &mut Vec<u8>) {
         bytes.extend_from_slice(&self.standard_header.to_bytes());
         if let Some(ref ext) = self.extended_header {
             bytes.extend_from_slice(&ext.to_bytes());
         }
         bytes.extend_from_slice(&self.payload);
-        bytes
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() < 20 {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DltParseError> {
+        DltMessageRef::from_bytes(bytes).map(|msg| msg.to_message())
+    }
+
+    // MSBF: payload is big endian
+    pub fn is_big_endian(&self) -> bool {
+        self.standard_header.big_endian
+    }
+
+    // MSIN_VERB; messages without an extended header are always non-verbose
+    pub fn is_verbose(&self) -> bool {
+        self.extended_header.as_ref().is_some_and(|ext| ext.msin.verbose)
+    }
+
+    // Message ID of a non-verbose message
+    pub fn message_id(&self) -> Option<u32> {
+        if self.is_verbose() {
+            return None;
+        }
+        decode_non_verbose(&self.payload, self.is_big_endian()).ok().map(|(id, _)| id)
+    }
+
+    // Packed argument data of a non-verbose message (after the message ID)
+    pub fn non_verbose_data(&self) -> Option<&[u8]> {
+        if self.is_verbose() {
             return None;
         }
+        decode_non_verbose(&self.payload, self.is_big_endian()).ok().map(|(_, data)| data)
+    }
 
-        let storage_header = DltStorageHeader::from_bytes(&bytes[0..16])?;
-        let standard_header = DltStandardHeader::from_bytes(&bytes[16..20])?;
+    // Decode the payload of a control request message (None if not a request)
+    pub fn control_request(&self) -> Option<Result<ControlRequest, DltParseError>> {
+        match self.message_info()?.kind {
+            MessageKind::Control(ControlType::Request) => {
+                Some(ControlRequest::from_bytes(&self.payload, self.is_big_endian()))
+            }
+            _ => None,
+        }
+    }
 
-        // DLT_HTYP_UEH = 0x01 (use extended header)
-        let has_extended = (standard_header.htyp & 0x01) != 0;
-        let (extended_header, payload_start) = if has_extended {
-            if bytes.len() < 30 {
-                return None;
+    // Decode the payload of a control response message (None if not a response)
+    pub fn control_response(&self) -> Option<Result<ControlResponse, DltParseError>> {
+        match self.message_info()?.kind {
+            MessageKind::Control(ControlType::Response) => {
+                Some(ControlResponse::from_bytes(&self.payload, self.is_big_endian()))
             }
-            (DltExtendedHeader::from_bytes(&bytes[20..30]), 30)
-        } else {
-            (None, 20)
-        };
+            _ => None,
+        }
+    }
 
-        let payload = bytes[payload_start..].to_vec();
+    // Decode the payload of a verbose network trace message (None if not a network trace)
+    pub fn network_trace(&self) -> Option<(NetworkTraceType, NetworkTrace)> {
+        match self.message_info()? {
+            MessageInfo { verbose: true, kind: MessageKind::NwTrace(trace_type) } => {
+                Some((trace_type, NetworkTrace::from_arguments(&self.arguments().ok()?)?))
+            }
+            _ => None,
+        }
+    }
 
-        Some(DltMessage {
-            storage_header,
-            standard_header,
-            extended_header,
-            payload,
-        })
+    // Decode all verbose arguments announced by the extended header
+    // Non-verbose messages have no arguments; offsets are relative to the payload
+    pub fn arguments(&self) -> Result<Vec<DltArgument>, DltParseError> {
+        match &self.extended_header {
+            Some(ext) if ext.msin.verbose => decode_arguments(&self.payload, ext.noar, self.is_big_endian()),
+            _ => Ok(Vec::new()),
+        }
     }
 
+    // Render all arguments separated by spaces (like dlt-viewer's payload column)
     pub fn extract_string_payload(&self) -> Option<String> {
-        if self.payload.len() > 6 {
-            Some(String::from_utf8_lossy(&self.payload[6..])
-                .trim_end_matches('\0')
-                .to_string())
-        } else {
-            None
+        if !self.is_verbose() {
+            return None;
         }
+        let args = self.arguments().ok()?;
+        let text: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Some(text.join(" "))
     }
 }
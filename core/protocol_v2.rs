@@ -0,0 +1,556 @@
+// DLT protocol version 2 (AUTOSAR R19-11 and later)
+// Base header: HTYP2 (32 bit), MCNT, LEN, then fields selected by the content info
+// (CNTI) and extension fields selected by HTYP2 flags. IDs are variable length.
+// Multi-byte header fields are big endian. HTYP2 is sent least significant byte
+// first, so VERS sits in bits 5-7 of the first header byte as in version 1.
+// Version 2 has no MSBF flag; payloads built here are little endian.
+// Frames keep the version 1 storage header, so files and streams can mix versions.
+use crate::clock::DltClock;
+#[cfg(feature = "std")]
+use crate::clock::SystemClock;
+use crate::control::{ControlRequest, ControlResponse};
+use crate::error::{DltConvertError, DltEncodeError, DltParseError};
+use crate::nonverbose::decode_non_verbose;
+use crate::protocol::{
+    DltExtendedHeader, DltMessage, DltStandardHeader, DltStorageHeader, DLT_HTYP_VERS,
+    DLT_SERIAL_HEADER_PATTERN,
+};
+use crate::types::{AppId, ContextId, ControlType, EcuId, LogLevel, MessageInfo, MessageKind};
+use crate::verbose::{argument_count, decode_arguments, encode_arguments, DltArgument, PayloadReader, PayloadWriter};
+use alloc::{string::{String, ToString}, vec::Vec};
+
+pub const DLT_PROTOCOL_VERSION_2: u8 = 2;
+
+// HTYP2 flags
+pub const DLT_HTYP2_CNTI: u32 = 0x0003;  // Content info (ContentInfo)
+pub const DLT_HTYP2_WEID: u32 = 0x0004;  // With ECU ID
+pub const DLT_HTYP2_WACID: u32 = 0x0008; // With application and context ID
+pub const DLT_HTYP2_WSID: u32 = 0x0010;  // With session ID
+pub const DLT_HTYP2_VERS: u32 = DLT_HTYP_VERS as u32;
+pub const DLT_HTYP2_WSFLN: u32 = 0x0100; // With source file name and line number
+pub const DLT_HTYP2_WTGS: u32 = 0x0200;  // With tags
+pub const DLT_HTYP2_WPVL: u32 = 0x0400;  // With privacy level
+pub const DLT_HTYP2_WSGM: u32 = 0x0800;  // With segmentation
+
+// HTYP2 + MCNT + LEN
+pub const DLT_V2_BASE_HEADER_SIZE: usize = 7;
+
+// Payload byte order of version 2 messages built by this crate
+const V2_BIG_ENDIAN: bool = false;
+
+// CNTI: selects the conditional base header fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ContentInfo {
+    Verbose = 0,    // MSIN, NOAR, TMSP2
+    NonVerbose = 1, // MSIN, TMSP2, MSID
+    Control = 2,    // MSIN, NOAR
+}
+
+impl ContentInfo {
+    pub fn from_u8(val: u8) -> Option<Self> {
+        match val {
+            0 => Some(ContentInfo::Verbose),
+            1 => Some(ContentInfo::NonVerbose),
+            2 => Some(ContentInfo::Control),
+            _ => None,
+        }
+    }
+
+    // Size of the conditional fields following MCNT and LEN
+    pub fn fields_size(&self) -> usize {
+        match self {
+            ContentInfo::Verbose => 1 + 1 + 9,
+            ContentInfo::NonVerbose => 1 + 9 + 4,
+            ContentInfo::Control => 1 + 1,
+        }
+    }
+}
+
+// TMSP2: 40-bit seconds and nanoseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DltTimestampV2 {
+    pub seconds: u64,
+    pub nanoseconds: u32,
+}
+
+impl DltTimestampV2 {
+    // From a version 1 timestamp (0.1 ms ticks)
+    pub fn from_ticks(ticks: u32) -> Self {
+        DltTimestampV2 {
+            seconds: (ticks / 10_000) as u64,
+            nanoseconds: (ticks % 10_000) * 100_000,
+        }
+    }
+
+    // To a version 1 timestamp (0.1 ms ticks), saturating
+    pub fn to_ticks(&self) -> u32 {
+        let ticks = self.seconds.saturating_mul(10_000) + (self.nanoseconds / 100_000) as u64;
+        ticks.min(u32::MAX as u64) as u32
+    }
+}
+
+// SFLN extension field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DltSourceLocation {
+    pub file: String,
+    pub line: u32,
+}
+
+// SGMT extension field: one frame of a segmented message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DltSegment {
+    First { total_len: u64 },
+    Consecutive { sequence: u32 },
+    Last,
+    Abort { reason: u8 },
+}
+
+impl DltSegment {
+    fn frame_type(&self) -> u8 {
+        match self {
+            DltSegment::First { .. } => 0,
+            DltSegment::Consecutive { .. } => 1,
+            DltSegment::Last => 2,
+            DltSegment::Abort { .. } => 3,
+        }
+    }
+}
+
+// Complete version 2 message
+#[derive(Debug, Clone)]
+pub struct DltMessageV2 {
+    pub storage_header: DltStorageHeader,
+    pub content: ContentInfo,
+    pub mcnt: u8,
+    pub msin: MessageInfo,
+    pub noar: u8,                    // Verbose and control messages
+    pub timestamp: DltTimestampV2,   // Verbose and non-verbose messages
+    pub message_id: u32,             // Non-verbose messages
+    pub ecu: Option<String>,
+    pub apid: Option<String>,        // APID and CTID are sent together (WACID)
+    pub ctid: Option<String>,
+    pub session_id: Option<u32>,
+    pub source: Option<DltSourceLocation>,
+    pub tags: Vec<String>,
+    pub privacy_level: Option<u8>,
+    pub segment: Option<DltSegment>,
+    pub payload: Vec<u8>,
+}
+
+// Constructors stamped with the system time
+#[cfg(feature = "std")]
+impl DltMessageV2 {
+    pub fn new_verbose_args(ecu: &str, apid: &str, ctid: &str, args: &[DltArgument]) -> Result<Self, DltEncodeError> {
+        Self::new_verbose_args_with_clock(ecu, apid, ctid, args, &SystemClock)
+    }
+
+    pub fn new_non_verbose(
+        ecu: &str,
+        apid: &str,
+        ctid: &str,
+        message_id: u32,
+        data: &[u8],
+    ) -> Result<Self, DltEncodeError> {
+        Self::new_non_verbose_with_clock(ecu, apid, ctid, message_id, data, &SystemClock)
+    }
+}
+
+impl DltMessageV2 {
+    pub fn new_verbose_args_with_clock(
+        ecu: &str,
+        apid: &str,
+        ctid: &str,
+        args: &[DltArgument],
+        clock: &dyn DltClock,
+    ) -> Result<Self, DltEncodeError> {
+        Ok(DltMessageV2 {
+            noar: argument_count(args)?,
+            payload: encode_arguments(args, V2_BIG_ENDIAN)?,
+            ..Self::empty(ecu, apid, ctid, clock)?
+        })
+    }
+
+    // Non-verbose message; `data` holds the packed arguments (the ID is a header field)
+    pub fn new_non_verbose_with_clock(
+        ecu: &str,
+        apid: &str,
+        ctid: &str,
+        message_id: u32,
+        data: &[u8],
+        clock: &dyn DltClock,
+    ) -> Result<Self, DltEncodeError> {
+        Ok(DltMessageV2 {
+            content: ContentInfo::NonVerbose,
+            msin: MessageInfo::log(LogLevel::Info, false),
+            message_id,
+            payload: data.to_vec(),
+            ..Self::empty(ecu, apid, ctid, clock)?
+        })
+    }
+
+    // Verbose message without arguments; the ECU ID must fit the storage header
+    fn empty(ecu: &str, apid: &str, ctid: &str, clock: &dyn DltClock) -> Result<Self, DltEncodeError> {
+        Ok(DltMessageV2 {
+            storage_header: DltStorageHeader::with_clock(EcuId::try_new(ecu)?, clock),
+            content: ContentInfo::Verbose,
+            mcnt: 0,
+            msin: MessageInfo::default(),
+            noar: 0,
+            timestamp: DltTimestampV2::default(),
+            message_id: 0,
+            ecu: Some(ecu.to_string()),
+            apid: Some(apid.to_string()),
+            ctid: Some(ctid.to_string()),
+            session_id: None,
+            source: None,
+            tags: Vec::new(),
+            privacy_level: None,
+            segment: None,
+            payload: Vec::new(),
+        })
+    }
+
+    // Only log messages have a level; other message types are returned unchanged
+    pub fn with_log_level(mut self, level: LogLevel) -> Self {
+        if let MessageKind::Log(ref mut msg_level) = self.msin.kind {
+            *msg_level = level;
+        }
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: DltTimestampV2) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_session_id(mut self, session_id: u32) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    pub fn with_source(mut self, file: &str, line: u32) -> Self {
+        self.source = Some(DltSourceLocation { file: file.to_string(), line });
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn with_privacy_level(mut self, level: u8) -> Self {
+        self.privacy_level = Some(level);
+        self
+    }
+
+    pub fn with_segment(mut self, segment: DltSegment) -> Self {
+        self.segment = Some(segment);
+        self
+    }
+
+    pub fn htyp2(&self) -> u32 {
+        let mut htyp2 = self.content as u32 | ((DLT_PROTOCOL_VERSION_2 as u32) << 5);
+        if self.ecu.is_some() {
+            htyp2 |= DLT_HTYP2_WEID;
+        }
+        if self.apid.is_some() || self.ctid.is_some() {
+            htyp2 |= DLT_HTYP2_WACID;
+        }
+        if self.session_id.is_some() {
+            htyp2 |= DLT_HTYP2_WSID;
+        }
+        if self.source.is_some() {
+            htyp2 |= DLT_HTYP2_WSFLN;
+        }
+        if !self.tags.is_empty() {
+            htyp2 |= DLT_HTYP2_WTGS;
+        }
+        if self.privacy_level.is_some() {
+            htyp2 |= DLT_HTYP2_WPVL;
+        }
+        if self.segment.is_some() {
+            htyp2 |= DLT_HTYP2_WSGM;
+        }
+        htyp2
+    }
+
+    // Message bytes without the storage header; LEN covers all of them. Fails if
+    // they do not fit LEN, or a string or the tag count does not fit its 8-bit field.
+    fn message_bytes(&self) -> Result<Vec<u8>, DltEncodeError> {
+        let mut w = PayloadWriter::new(true);
+        w.bytes(&self.htyp2().to_le_bytes());
+        w.bytes(&[self.mcnt]);
+        w.u16(0); // LEN, patched below
+
+        w.bytes(&[self.msin.to_byte()]);
+        if self.content != ContentInfo::NonVerbose {
+            w.bytes(&[self.noar]);
+        }
+        if self.content != ContentInfo::Control {
+            w.u32(self.timestamp.nanoseconds);
+            w.bytes(&self.timestamp.seconds.to_be_bytes()[3..]);
+        }
+        if self.content == ContentInfo::NonVerbose {
+            w.u32(self.message_id);
+        }
+
+        if let Some(ref ecu) = self.ecu {
+            write_short_str(&mut w, ecu)?;
+        }
+        if self.apid.is_some() || self.ctid.is_some() {
+            write_short_str(&mut w, self.apid.as_deref().unwrap_or(""))?;
+            write_short_str(&mut w, self.ctid.as_deref().unwrap_or(""))?;
+        }
+        if let Some(session_id) = self.session_id {
+            w.u32(session_id);
+        }
+        if let Some(ref source) = self.source {
+            write_short_str(&mut w, &source.file)?;
+            w.u32(source.line);
+        }
+        if !self.tags.is_empty() {
+            w.bytes(&[short_len(self.tags.len())?]);
+            for tag in &self.tags {
+                write_short_str(&mut w, tag)?;
+            }
+        }
+        if let Some(level) = self.privacy_level {
+            w.bytes(&[level]);
+        }
+        if let Some(segment) = self.segment {
+            w.bytes(&[segment.frame_type()]);
+            match segment {
+                DltSegment::First { total_len } => w.u64(total_len),
+                DltSegment::Consecutive { sequence } => w.u32(sequence),
+                DltSegment::Last => {}
+                DltSegment::Abort { reason } => w.bytes(&[reason]),
+            }
+        }
+
+        w.bytes(&self.payload);
+        let mut bytes = w.finish();
+        let len = u16::try_from(bytes.len()).map_err(|_| DltEncodeError::MessageTooLong { len: bytes.len() })?;
+        bytes[5..7].copy_from_slice(&len.to_be_bytes());
+        Ok(bytes)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DltEncodeError> {
+        let mut bytes = self.storage_header.to_bytes();
+        bytes.extend_from_slice(&self.message_bytes()?);
+        Ok(bytes)
+    }
+
+    // Serial link framing: "DLS\x01" instead of the storage header
+    pub fn to_serial_bytes(&self) -> Result<Vec<u8>, DltEncodeError> {
+        let mut bytes = DLT_SERIAL_HEADER_PATTERN.to_vec();
+        bytes.extend_from_slice(&self.message_bytes()?);
+        Ok(bytes)
+    }
+
+    // Decode a storage header followed by a version 2 message; trailing data is ignored
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DltParseError> {
+        let storage_header = DltStorageHeader::from_bytes(bytes)?;
+        let message = &bytes[16..];
+        DltParseError::check_len(message, 0, DLT_V2_BASE_HEADER_SIZE).map_err(|e| e.rebase(16))?;
+        let len = u16::from_be_bytes([message[5], message[6]]) as usize;
+        DltParseError::check_len(message, 0, len).map_err(|e| e.rebase(16))?;
+        Self::decode(storage_header, &message[..len]).map_err(|e| e.rebase(16))
+    }
+
+    fn decode(storage_header: DltStorageHeader, message: &[u8]) -> Result<Self, DltParseError> {
+        let mut r = PayloadReader::new(message, true);
+        let htyp2 = u32::from_le_bytes(r.id()?);
+        let version = ((htyp2 & DLT_HTYP2_VERS) >> 5) as u8;
+        if version != DLT_PROTOCOL_VERSION_2 {
+            return Err(DltParseError::UnsupportedVersion { offset: 0, version });
+        }
+        let cnti = (htyp2 & DLT_HTYP2_CNTI) as u8;
+        let content = ContentInfo::from_u8(cnti)
+            .ok_or(DltParseError::UnknownContentInfo { offset: 0, cnti })?;
+        let mcnt = r.u8()?;
+        let declared = r.u16()? as usize;
+        let minimum = DLT_V2_BASE_HEADER_SIZE + content.fields_size();
+        if declared < minimum {
+            return Err(DltParseError::LengthMismatch { offset: 0, declared, minimum });
+        }
+
+        let msin = MessageInfo::from_byte(r.u8()?);
+        let noar = if content != ContentInfo::NonVerbose { r.u8()? } else { 0 };
+        let timestamp = if content != ContentInfo::Control {
+            let nanoseconds = r.u32()?;
+            let mut seconds = [0u8; 8];
+            seconds[3..].copy_from_slice(r.take(5)?);
+            DltTimestampV2 { seconds: u64::from_be_bytes(seconds), nanoseconds }
+        } else {
+            DltTimestampV2::default()
+        };
+        let message_id = if content == ContentInfo::NonVerbose { r.u32()? } else { 0 };
+
+        let ecu = if htyp2 & DLT_HTYP2_WEID != 0 { Some(read_short_str(&mut r)?) } else { None };
+        let (apid, ctid) = if htyp2 & DLT_HTYP2_WACID != 0 {
+            (Some(read_short_str(&mut r)?), Some(read_short_str(&mut r)?))
+        } else {
+            (None, None)
+        };
+        let session_id = if htyp2 & DLT_HTYP2_WSID != 0 { Some(r.u32()?) } else { None };
+        let source = if htyp2 & DLT_HTYP2_WSFLN != 0 {
+            let file = read_short_str(&mut r)?;
+            Some(DltSourceLocation { file, line: r.u32()? })
+        } else {
+            None
+        };
+        let mut tags = Vec::new();
+        if htyp2 & DLT_HTYP2_WTGS != 0 {
+            for _ in 0..r.u8()? {
+                tags.push(read_short_str(&mut r)?);
+            }
+        }
+        let privacy_level = if htyp2 & DLT_HTYP2_WPVL != 0 { Some(r.u8()?) } else { None };
+        let segment = if htyp2 & DLT_HTYP2_WSGM != 0 {
+            let offset = r.pos;
+            Some(match r.u8()? {
+                0 => DltSegment::First { total_len: r.u64()? },
+                1 => DltSegment::Consecutive { sequence: r.u32()? },
+                2 => DltSegment::Last,
+                3 => DltSegment::Abort { reason: r.u8()? },
+                frame_type => return Err(DltParseError::UnknownSegmentType { offset, frame_type }),
+            })
+        } else {
+            None
+        };
+
+        Ok(DltMessageV2 {
+            storage_header,
+            content,
+            mcnt,
+            msin,
+            noar,
+            timestamp,
+            message_id,
+            ecu,
+            apid,
+            ctid,
+            session_id,
+            source,
+            tags,
+            privacy_level,
+            segment,
+            payload: message[r.pos..].to_vec(),
+        })
+    }
+
+    // Decode all verbose arguments; non-verbose and control messages have none
+    pub fn arguments(&self) -> Result<Vec<DltArgument>, DltParseError> {
+        match self.content {
+            ContentInfo::Verbose => decode_arguments(&self.payload, self.noar, V2_BIG_ENDIAN),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    // Convert a version 1 message. Big-endian verbose and control payloads are
+    // re-encoded; packed non-verbose data is copied as is, since its layout
+    // is only known to the catalog.
+    pub fn from_v1(msg: &DltMessage) -> Result<Self, DltConvertError> {
+        let big_endian = msg.is_big_endian();
+        let msin = msg.message_info().unwrap_or(MessageInfo::log(LogLevel::Info, false));
+        let content = match msin.kind {
+            MessageKind::Control(_) => ContentInfo::Control,
+            _ if msin.verbose => ContentInfo::Verbose,
+            _ => ContentInfo::NonVerbose,
+        };
+
+        let mut message_id = 0;
+        let payload = match content {
+            ContentInfo::Verbose if big_endian => encode_arguments(&msg.arguments()?, V2_BIG_ENDIAN)?,
+            ContentInfo::Verbose => msg.payload.clone(),
+            ContentInfo::NonVerbose => {
+                let (id, data) = decode_non_verbose(&msg.payload, big_endian)?;
+                message_id = id;
+                data.to_vec()
+            }
+            ContentInfo::Control if !big_endian => msg.payload.clone(),
+            ContentInfo::Control => match msin.kind {
+                MessageKind::Control(ControlType::Request) => {
+                    ControlRequest::from_bytes(&msg.payload, true)?.to_bytes(V2_BIG_ENDIAN)?
+                }
+                _ => ControlResponse::from_bytes(&msg.payload, true)?.to_bytes(V2_BIG_ENDIAN)?,
+            },
+        };
+
+        let ext = msg.extended_header.as_ref();
+        Ok(DltMessageV2 {
+            storage_header: msg.storage_header.clone(),
+            content,
+            mcnt: msg.standard_header.mcnt,
+            msin,
+            noar: ext.map_or(0, |ext| ext.noar),
+            timestamp: DltTimestampV2::from_ticks(msg.standard_header.timestamp.unwrap_or(0)),
+            message_id,
+            ecu: msg.standard_header.ecu.map(|ecu| ecu.as_str()),
+            apid: ext.map(|ext| ext.apid.as_str()),
+            ctid: ext.map(|ext| ext.ctid.as_str()),
+            session_id: msg.standard_header.session_id,
+            source: None,
+            tags: Vec::new(),
+            privacy_level: None,
+            segment: None,
+            payload,
+        })
+    }
+
+    // Convert to a version 1 message. Source location, tags, privacy level and
+    // segmentation have no version 1 field and are dropped. A zero timestamp becomes
+    // "no timestamp". Fails if an ID does not fit its 4-byte field or the payload is
+    // too long for the version 1 headers.
+    pub fn to_v1(&self) -> Result<DltMessage, DltEncodeError> {
+        let mut standard_header = DltStandardHeader::new(true, self.mcnt, 0);
+        if let Some(ref ecu) = self.ecu {
+            standard_header = standard_header.with_ecu_id(EcuId::try_new(ecu)?);
+        }
+        if let Some(session_id) = self.session_id {
+            standard_header = standard_header.with_session_id(session_id);
+        }
+        if self.timestamp != DltTimestampV2::default() {
+            standard_header = standard_header.with_timestamp(self.timestamp.to_ticks());
+        }
+
+        let apid = self.apid.as_deref().map(AppId::try_new).transpose()?.unwrap_or_default();
+        let ctid = self.ctid.as_deref().map(ContextId::try_new).transpose()?.unwrap_or_default();
+        let payload = match self.content {
+            ContentInfo::NonVerbose => {
+                let mut w = PayloadWriter::new(V2_BIG_ENDIAN);
+                w.u32(self.message_id);
+                w.bytes(&self.payload);
+                w.finish()
+            }
+            _ => self.payload.clone(),
+        };
+
+        let mut msg = DltMessage {
+            storage_header: self.storage_header.clone(),
+            standard_header: standard_header.with_big_endian(V2_BIG_ENDIAN),
+            extended_header: Some(DltExtendedHeader::new(apid, ctid, self.noar).with_message_info(self.msin)),
+            payload,
+        };
+        msg.update_length()?;
+        Ok(msg)
+    }
+}
+
+// Length-prefixed string (IDs, file name, tags) of at most 255 bytes
+fn write_short_str(w: &mut PayloadWriter, s: &str) -> Result<(), DltEncodeError> {
+    w.bytes(&[short_len(s.len())?]);
+    w.bytes(s.as_bytes());
+    Ok(())
+}
+
+// String length or tag count in an 8-bit field
+fn short_len(len: usize) -> Result<u8, DltEncodeError> {
+    u8::try_from(len).map_err(|_| DltEncodeError::FieldTooLong { len, max: u8::MAX as usize })
+}
+
+fn read_short_str(r: &mut PayloadReader) -> Result<String, DltParseError> {
+    let len = r.u8()? as usize;
+    Ok(String::from_utf8_lossy(r.take(len)?).into_owned())
+}
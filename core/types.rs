@@ -1,56 +1,117 @@
 // Core types used across all DLT components
-use std::fmt;
+use core::fmt;
+use core::str::FromStr;
+use alloc::string::{String, ToString};
 
-// Application ID (4 bytes)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct AppId(pub [u8; 4]);
-
-impl AppId {
-    pub fn new(s: &str) -> Self {
-        let mut id = [0u8; 4];
-        let bytes = s.as_bytes();
-        let len = bytes.len().min(4);
-        id[..len].copy_from_slice(&bytes[..len]);
-        AppId(id)
-    }
+// Why a string or byte field is not a valid 4-byte DLT ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DltIdError {
+    Empty,
+    TooLong { len: usize },
+    // IDs are printable ASCII without spaces; trailing NULs are padding
+    InvalidByte { index: usize, byte: u8 },
+}
 
-    pub fn as_str(&self) -> String {
-        String::from_utf8_lossy(&self.0).trim_end_matches('\0').to_string()
+impl fmt::Display for DltIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DltIdError::Empty => write!(f, "empty ID"),
+            DltIdError::TooLong { len } => write!(f, "ID is {} bytes long, at most 4 allowed", len),
+            DltIdError::InvalidByte { index, byte } => {
+                write!(f, "invalid byte 0x{:02x} at index {} (printable ASCII only)", byte, index)
+            }
+        }
     }
 }
 
-// Context ID (4 bytes)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct ContextId(pub [u8; 4]);
+impl core::error::Error for DltIdError {}
 
-impl ContextId {
-    pub fn new(s: &str) -> Self {
-        let mut id = [0u8; 4];
-        let bytes = s.as_bytes();
-        let len = bytes.len().min(4);
-        id[..len].copy_from_slice(&bytes[..len]);
-        ContextId(id)
+// Validate an ID and pad it with NULs to 4 bytes
+const fn validate_id(bytes: &[u8]) -> Result<[u8; 4], DltIdError> {
+    let mut len = bytes.len();
+    while len > 0 && bytes[len - 1] == 0 {
+        len -= 1;
     }
-
-    pub fn as_str(&self) -> String {
-        String::from_utf8_lossy(&self.0).trim_end_matches('\0').to_string()
+    if len == 0 {
+        return Err(DltIdError::Empty);
     }
+    if len > 4 {
+        return Err(DltIdError::TooLong { len });
+    }
+    let mut id = [0u8; 4];
+    let mut i = 0;
+    while i < len {
+        if !bytes[i].is_ascii_graphic() {
+            return Err(DltIdError::InvalidByte { index: i, byte: bytes[i] });
+        }
+        id[i] = bytes[i];
+        i += 1;
+    }
+    Ok(id)
 }
 
-// ECU ID (4 bytes)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct EcuId(pub [u8; 4]);
+// 4-byte ID type: validating constructors, parsing and display
+macro_rules! dlt_id {
+    ($name:ident, $what:literal) => {
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub [u8; 4]);
 
-impl EcuId {
-    pub fn new(s: &str) -> Self {
-        let mut id = [0u8; 4];
-        let bytes = s.as_bytes();
-        let len = bytes.len().min(4);
-        id[..len].copy_from_slice(&bytes[..len]);
-        EcuId(id)
-    }
+        impl $name {
+            // 1-4 printable ASCII characters
+            pub const fn try_new(s: &str) -> Result<Self, DltIdError> {
+                match validate_id(s.as_bytes()) {
+                    Ok(id) => Ok($name(id)),
+                    Err(e) => Err(e),
+                }
+            }
+
+            // For constants: an invalid ID fails compilation in const context
+            pub const fn new_const(s: &str) -> Self {
+                match Self::try_new(s) {
+                    Ok(id) => id,
+                    Err(_) => panic!(concat!("invalid ", $what)),
+                }
+            }
+
+            pub fn as_str(&self) -> String {
+                String::from_utf8_lossy(&self.0).trim_end_matches('\0').to_string()
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = DltIdError;
+
+            fn from_str(s: &str) -> Result<Self, DltIdError> {
+                Self::try_new(s)
+            }
+        }
+
+        // Header field bytes, NUL padded
+        impl TryFrom<&[u8]> for $name {
+            type Error = DltIdError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, DltIdError> {
+                validate_id(bytes).map($name)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.as_str())
+            }
+        }
+    };
 }
 
+// Application ID (4 bytes)
+dlt_id!(AppId, "application ID");
+
+// Context ID (4 bytes)
+dlt_id!(ContextId, "context ID");
+
+// ECU ID (4 bytes)
+dlt_id!(EcuId, "ECU ID");
+
 // Log levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
@@ -85,3 +146,248 @@ pub enum MessageType {
     NwTrace = 2,
     Control = 3,
 }
+
+impl LogLevel {
+    pub fn from_u8(val: u8) -> Option<Self> {
+        match val {
+            1 => Some(LogLevel::Fatal),
+            2 => Some(LogLevel::Error),
+            3 => Some(LogLevel::Warn),
+            4 => Some(LogLevel::Info),
+            5 => Some(LogLevel::Debug),
+            6 => Some(LogLevel::Verbose),
+            _ => None,
+        }
+    }
+
+    // Lowercase name as printed by the COVESA tools
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Fatal => "fatal",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Verbose => "verbose",
+        }
+    }
+}
+
+impl MessageType {
+    pub fn from_u8(val: u8) -> Option<Self> {
+        match val {
+            0 => Some(MessageType::Log),
+            1 => Some(MessageType::AppTrace),
+            2 => Some(MessageType::NwTrace),
+            3 => Some(MessageType::Control),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageType::Log => "log",
+            MessageType::AppTrace => "app_trace",
+            MessageType::NwTrace => "nw_trace",
+            MessageType::Control => "control",
+        }
+    }
+}
+
+// Application trace subtype (MTIN for MSTP = AppTrace)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TraceType {
+    Variable = 1,
+    FunctionIn = 2,
+    FunctionOut = 3,
+    State = 4,
+    Vfb = 5,
+}
+
+impl TraceType {
+    pub fn from_u8(val: u8) -> Option<Self> {
+        match val {
+            1 => Some(TraceType::Variable),
+            2 => Some(TraceType::FunctionIn),
+            3 => Some(TraceType::FunctionOut),
+            4 => Some(TraceType::State),
+            5 => Some(TraceType::Vfb),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TraceType::Variable => "variable",
+            TraceType::FunctionIn => "func_in",
+            TraceType::FunctionOut => "func_out",
+            TraceType::State => "state",
+            TraceType::Vfb => "vfb",
+        }
+    }
+}
+
+// Network trace subtype (MTIN for MSTP = NwTrace)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum NetworkTraceType {
+    Ipc = 1,
+    Can = 2,
+    FlexRay = 3,
+    Most = 4,
+    Ethernet = 5,
+    SomeIp = 6,
+}
+
+impl NetworkTraceType {
+    pub fn from_u8(val: u8) -> Option<Self> {
+        match val {
+            1 => Some(NetworkTraceType::Ipc),
+            2 => Some(NetworkTraceType::Can),
+            3 => Some(NetworkTraceType::FlexRay),
+            4 => Some(NetworkTraceType::Most),
+            5 => Some(NetworkTraceType::Ethernet),
+            6 => Some(NetworkTraceType::SomeIp),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NetworkTraceType::Ipc => "ipc",
+            NetworkTraceType::Can => "can",
+            NetworkTraceType::FlexRay => "flexray",
+            NetworkTraceType::Most => "most",
+            NetworkTraceType::Ethernet => "ethernet",
+            NetworkTraceType::SomeIp => "someip",
+        }
+    }
+}
+
+// Control message subtype (MTIN for MSTP = Control)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ControlType {
+    Request = 1,
+    Response = 2,
+}
+
+impl ControlType {
+    pub fn from_u8(val: u8) -> Option<Self> {
+        match val {
+            1 => Some(ControlType::Request),
+            2 => Some(ControlType::Response),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControlType::Request => "request",
+            ControlType::Response => "response",
+        }
+    }
+}
+
+// Message type together with its typed subtype
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Log(LogLevel),
+    AppTrace(TraceType),
+    NwTrace(NetworkTraceType),
+    Control(ControlType),
+    // MSTP/MTIN combination not defined by the spec, kept for round-tripping
+    Unknown { mstp: u8, mtin: u8 },
+}
+
+// MSIN (message info) byte of the extended header:
+// bit 0 = VERB, bits 1-3 = MSTP, bits 4-7 = MTIN
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageInfo {
+    pub verbose: bool,
+    pub kind: MessageKind,
+}
+
+impl MessageInfo {
+    pub fn new(verbose: bool, kind: MessageKind) -> Self {
+        MessageInfo { verbose, kind }
+    }
+
+    pub fn log(level: LogLevel, verbose: bool) -> Self {
+        MessageInfo::new(verbose, MessageKind::Log(level))
+    }
+
+    pub fn message_type(&self) -> Option<MessageType> {
+        match self.kind {
+            MessageKind::Log(_) => Some(MessageType::Log),
+            MessageKind::AppTrace(_) => Some(MessageType::AppTrace),
+            MessageKind::NwTrace(_) => Some(MessageType::NwTrace),
+            MessageKind::Control(_) => Some(MessageType::Control),
+            MessageKind::Unknown { mstp, .. } => MessageType::from_u8(mstp),
+        }
+    }
+
+    pub fn log_level(&self) -> Option<LogLevel> {
+        match self.kind {
+            MessageKind::Log(level) => Some(level),
+            _ => None,
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        let (mstp, mtin) = match self.kind {
+            MessageKind::Log(level) => (MessageType::Log as u8, level as u8),
+            MessageKind::AppTrace(t) => (MessageType::AppTrace as u8, t as u8),
+            MessageKind::NwTrace(t) => (MessageType::NwTrace as u8, t as u8),
+            MessageKind::Control(t) => (MessageType::Control as u8, t as u8),
+            MessageKind::Unknown { mstp, mtin } => (mstp, mtin),
+        };
+        ((mtin & 0x0f) << 4) | ((mstp & 0x07) << 1) | self.verbose as u8
+    }
+
+    pub fn from_byte(msin: u8) -> Self {
+        let verbose = msin & 0x01 != 0;
+        let mstp = (msin >> 1) & 0x07;
+        let mtin = msin >> 4;
+        let kind = match MessageType::from_u8(mstp) {
+            Some(MessageType::Log) => LogLevel::from_u8(mtin).map(MessageKind::Log),
+            Some(MessageType::AppTrace) => TraceType::from_u8(mtin).map(MessageKind::AppTrace),
+            Some(MessageType::NwTrace) => NetworkTraceType::from_u8(mtin).map(MessageKind::NwTrace),
+            Some(MessageType::Control) => ControlType::from_u8(mtin).map(MessageKind::Control),
+            None => None,
+        };
+        MessageInfo {
+            verbose,
+            kind: kind.unwrap_or(MessageKind::Unknown { mstp, mtin }),
+        }
+    }
+
+    pub fn type_str(&self) -> &'static str {
+        self.message_type().map(|t| t.as_str()).unwrap_or("unknown")
+    }
+
+    pub fn subtype_str(&self) -> &'static str {
+        match self.kind {
+            MessageKind::Log(level) => level.as_str(),
+            MessageKind::AppTrace(t) => t.as_str(),
+            MessageKind::NwTrace(t) => t.as_str(),
+            MessageKind::Control(t) => t.as_str(),
+            MessageKind::Unknown { .. } => "unknown",
+        }
+    }
+}
+
+impl Default for MessageInfo {
+    fn default() -> Self {
+        MessageInfo::log(LogLevel::Info, true)
+    }
+}
+
+// "log warn V" - type, subtype and verbose flag as printed by dlt-receive
+impl fmt::Display for MessageInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mode = if self.verbose { "V" } else { "N" };
+        write!(f, "{} {} {}", self.type_str(), self.subtype_str(), mode)
+    }
+}
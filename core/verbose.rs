@@ -0,0 +1,597 @@
+// Verbose mode payload: typed arguments described by a 32-bit type info field
+use crate::error::{DltEncodeError, DltParseError};
+use core::fmt;
+use alloc::{string::{String, ToString}, vec::Vec};
+
+// Type info field layout (PRS_Dlt_00354 ff.)
+pub const DLT_TYPE_INFO_TYLE: u32 = 0x0000_000f; // Length of standard data type
+pub const DLT_TYPE_INFO_BOOL: u32 = 0x0000_0010;
+pub const DLT_TYPE_INFO_SINT: u32 = 0x0000_0020;
+pub const DLT_TYPE_INFO_UINT: u32 = 0x0000_0040;
+pub const DLT_TYPE_INFO_FLOA: u32 = 0x0000_0080;
+pub const DLT_TYPE_INFO_ARAY: u32 = 0x0000_0100;
+pub const DLT_TYPE_INFO_STRG: u32 = 0x0000_0200;
+pub const DLT_TYPE_INFO_RAWD: u32 = 0x0000_0400;
+pub const DLT_TYPE_INFO_VARI: u32 = 0x0000_0800; // Variable info (name/unit)
+pub const DLT_TYPE_INFO_FIXP: u32 = 0x0000_1000; // Fixed point (quantization/offset)
+pub const DLT_TYPE_INFO_TRAI: u32 = 0x0000_2000; // Trace info
+pub const DLT_TYPE_INFO_STRU: u32 = 0x0000_4000;
+pub const DLT_TYPE_INFO_SCOD: u32 = 0x0003_8000; // String coding
+
+pub const DLT_TYLE_8BIT: u32 = 0x1;
+pub const DLT_TYLE_16BIT: u32 = 0x2;
+pub const DLT_TYLE_32BIT: u32 = 0x3;
+pub const DLT_TYLE_64BIT: u32 = 0x4;
+pub const DLT_TYLE_128BIT: u32 = 0x5;
+
+pub const DLT_SCOD_ASCII: u32 = 0x0000_0000;
+pub const DLT_SCOD_UTF8: u32 = 0x0000_8000;
+pub const DLT_SCOD_HEX: u32 = 0x0001_0000;
+pub const DLT_SCOD_BIN: u32 = 0x0001_8000;
+
+// Coding of STRG arguments (ASCII/UTF-8) and display hint for UINT (HEX/BIN)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Coding {
+    #[default]
+    Ascii,
+    Utf8,
+    Hex,
+    Bin,
+}
+
+impl Coding {
+    fn to_scod(self) -> u32 {
+        match self {
+            Coding::Ascii => DLT_SCOD_ASCII,
+            Coding::Utf8 => DLT_SCOD_UTF8,
+            Coding::Hex => DLT_SCOD_HEX,
+            Coding::Bin => DLT_SCOD_BIN,
+        }
+    }
+
+    fn from_scod(type_info: u32) -> Self {
+        match type_info & DLT_TYPE_INFO_SCOD {
+            DLT_SCOD_UTF8 => Coding::Utf8,
+            DLT_SCOD_HEX => Coding::Hex,
+            DLT_SCOD_BIN => Coding::Bin,
+            _ => Coding::Ascii,
+        }
+    }
+}
+
+// Argument value - one variant per standard data type and width
+#[derive(Debug, Clone, PartialEq)]
+pub enum DltValue {
+    Bool(bool),
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Int128(i128),
+    UInt8(u8),
+    UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
+    UInt128(u128),
+    Float16(u16),       // Raw IEEE 754 binary16 bits
+    Float32(f32),
+    Float64(f64),
+    Float128([u8; 16]), // Raw IEEE 754 binary128 bits (no native Rust type)
+    String(String),
+    Raw(Vec<u8>),
+    Trace(String),
+}
+
+impl DltValue {
+    fn type_bits(&self) -> u32 {
+        match self {
+            DltValue::Bool(_) => DLT_TYPE_INFO_BOOL | DLT_TYLE_8BIT,
+            DltValue::Int8(_) => DLT_TYPE_INFO_SINT | DLT_TYLE_8BIT,
+            DltValue::Int16(_) => DLT_TYPE_INFO_SINT | DLT_TYLE_16BIT,
+            DltValue::Int32(_) => DLT_TYPE_INFO_SINT | DLT_TYLE_32BIT,
+            DltValue::Int64(_) => DLT_TYPE_INFO_SINT | DLT_TYLE_64BIT,
+            DltValue::Int128(_) => DLT_TYPE_INFO_SINT | DLT_TYLE_128BIT,
+            DltValue::UInt8(_) => DLT_TYPE_INFO_UINT | DLT_TYLE_8BIT,
+            DltValue::UInt16(_) => DLT_TYPE_INFO_UINT | DLT_TYLE_16BIT,
+            DltValue::UInt32(_) => DLT_TYPE_INFO_UINT | DLT_TYLE_32BIT,
+            DltValue::UInt64(_) => DLT_TYPE_INFO_UINT | DLT_TYLE_64BIT,
+            DltValue::UInt128(_) => DLT_TYPE_INFO_UINT | DLT_TYLE_128BIT,
+            DltValue::Float16(_) => DLT_TYPE_INFO_FLOA | DLT_TYLE_16BIT,
+            DltValue::Float32(_) => DLT_TYPE_INFO_FLOA | DLT_TYLE_32BIT,
+            DltValue::Float64(_) => DLT_TYPE_INFO_FLOA | DLT_TYLE_64BIT,
+            DltValue::Float128(_) => DLT_TYPE_INFO_FLOA | DLT_TYLE_128BIT,
+            DltValue::String(_) => DLT_TYPE_INFO_STRG,
+            DltValue::Raw(_) => DLT_TYPE_INFO_RAWD,
+            DltValue::Trace(_) => DLT_TYPE_INFO_TRAI,
+        }
+    }
+
+    fn is_integer(&self) -> bool {
+        self.type_bits() & (DLT_TYPE_INFO_SINT | DLT_TYPE_INFO_UINT) != 0
+    }
+
+    fn is_numeric(&self) -> bool {
+        self.is_integer() || (self.type_bits() & DLT_TYPE_INFO_FLOA) != 0
+    }
+
+    fn tyle(&self) -> u32 {
+        self.type_bits() & DLT_TYPE_INFO_TYLE
+    }
+}
+
+impl fmt::Display for DltValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DltValue::Bool(v) => write!(f, "{}", *v as u8),
+            DltValue::Int8(v) => write!(f, "{}", v),
+            DltValue::Int16(v) => write!(f, "{}", v),
+            DltValue::Int32(v) => write!(f, "{}", v),
+            DltValue::Int64(v) => write!(f, "{}", v),
+            DltValue::Int128(v) => write!(f, "{}", v),
+            DltValue::UInt8(v) => write!(f, "{}", v),
+            DltValue::UInt16(v) => write!(f, "{}", v),
+            DltValue::UInt32(v) => write!(f, "{}", v),
+            DltValue::UInt64(v) => write!(f, "{}", v),
+            DltValue::UInt128(v) => write!(f, "{}", v),
+            DltValue::Float16(bits) => write!(f, "{}", half_to_f32(*bits)),
+            DltValue::Float32(v) => write!(f, "{}", v),
+            DltValue::Float64(v) => write!(f, "{}", v),
+            DltValue::Float128(bits) => write_hex(f, bits),
+            DltValue::String(s) | DltValue::Trace(s) => write!(f, "{}", s),
+            DltValue::Raw(data) => write_hex(f, data),
+        }
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter, data: &[u8]) -> fmt::Result {
+    for (i, b) in data.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+// IEEE 754 binary16 -> binary32 (for display only)
+fn half_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = ((bits >> 10) & 0x1f) as i32;
+    let frac = (bits & 0x3ff) as f32;
+    match exp {
+        0 => sign * frac * pow2(-24),
+        0x1f if frac == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + frac / 1024.0) * pow2(exp - 15),
+    }
+}
+
+// 2^n for normal f32 exponents (f32::powi needs std)
+fn pow2(n: i32) -> f32 {
+    f32::from_bits(((n + 127) as u32) << 23)
+}
+
+// Fixed point scaling: physical = raw * quantization + offset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedPoint {
+    pub quantization: f32,
+    pub offset: i64,
+}
+
+// One verbose mode argument: value plus optional VARI/FIXP/SCOD attributes
+#[derive(Debug, Clone, PartialEq)]
+pub struct DltArgument {
+    pub value: DltValue,
+    pub name: Option<String>,           // VARI
+    pub unit: Option<String>,           // VARI (numeric types only)
+    pub fixed_point: Option<FixedPoint>, // FIXP (integer types only)
+    pub coding: Coding,
+}
+
+impl DltArgument {
+    pub fn new(value: DltValue) -> Self {
+        let coding = match value {
+            DltValue::String(ref s) if !s.is_ascii() => Coding::Utf8,
+            _ => Coding::Ascii,
+        };
+        DltArgument {
+            value,
+            name: None,
+            unit: None,
+            fixed_point: None,
+            coding,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    pub fn with_fixed_point(mut self, quantization: f32, offset: i64) -> Self {
+        self.fixed_point = Some(FixedPoint { quantization, offset });
+        self
+    }
+
+    pub fn with_coding(mut self, coding: Coding) -> Self {
+        self.coding = coding;
+        self
+    }
+
+    pub fn type_info(&self) -> u32 {
+        let mut type_info = self.value.type_bits();
+        if self.name.is_some() || self.unit.is_some() {
+            type_info |= DLT_TYPE_INFO_VARI;
+        }
+        if self.fixed_point.is_some() && self.value.is_integer() {
+            type_info |= DLT_TYPE_INFO_FIXP;
+        }
+        match self.value {
+            DltValue::String(_) | DltValue::UInt8(_) | DltValue::UInt16(_)
+            | DltValue::UInt32(_) | DltValue::UInt64(_) | DltValue::UInt128(_) => {
+                type_info |= self.coding.to_scod();
+            }
+            _ => {}
+        }
+        type_info
+    }
+
+    // Fails if a string, raw data, name or unit is too long for its 16-bit length field
+    pub fn to_bytes(&self, big_endian: bool) -> Result<Vec<u8>, DltEncodeError> {
+        let mut w = PayloadWriter::new(big_endian);
+        let type_info = self.type_info();
+        w.u32(type_info);
+
+        let name = self.name.as_deref().unwrap_or("");
+        let unit = self.unit.as_deref().unwrap_or("");
+        let vari = type_info & DLT_TYPE_INFO_VARI != 0;
+
+        match &self.value {
+            DltValue::String(s) | DltValue::Trace(s) => {
+                w.u16(field_len(s.len() + 1)?);
+                if vari {
+                    w.cstr_with_len(name)?;
+                }
+                w.bytes(s.as_bytes());
+                w.bytes(&[0]);
+                return Ok(w.finish());
+            }
+            DltValue::Raw(data) => {
+                w.u16(field_len(data.len())?);
+                if vari {
+                    w.cstr_with_len(name)?;
+                }
+                w.bytes(data);
+                return Ok(w.finish());
+            }
+            _ => {}
+        }
+
+        if vari {
+            if self.value.is_numeric() {
+                w.u16(field_len(name.len() + 1)?);
+                w.u16(field_len(unit.len() + 1)?);
+                w.bytes(name.as_bytes());
+                w.bytes(&[0]);
+                w.bytes(unit.as_bytes());
+                w.bytes(&[0]);
+            } else {
+                w.cstr_with_len(name)?;
+            }
+        }
+
+        if let Some(fp) = self.fixed_point.filter(|_| type_info & DLT_TYPE_INFO_FIXP != 0) {
+            w.u32(fp.quantization.to_bits());
+            match self.value.tyle() {
+                DLT_TYLE_64BIT => w.u64(fp.offset as u64),
+                DLT_TYLE_128BIT => w.u128(fp.offset as i128 as u128),
+                _ => w.u32(fp.offset as i32 as u32),
+            }
+        }
+
+        match self.value {
+            DltValue::Bool(v) => w.bytes(&[v as u8]),
+            DltValue::Int8(v) => w.bytes(&[v as u8]),
+            DltValue::Int16(v) => w.u16(v as u16),
+            DltValue::Int32(v) => w.u32(v as u32),
+            DltValue::Int64(v) => w.u64(v as u64),
+            DltValue::Int128(v) => w.u128(v as u128),
+            DltValue::UInt8(v) => w.bytes(&[v]),
+            DltValue::UInt16(v) => w.u16(v),
+            DltValue::UInt32(v) => w.u32(v),
+            DltValue::UInt64(v) => w.u64(v),
+            DltValue::UInt128(v) => w.u128(v),
+            DltValue::Float16(bits) => w.u16(bits),
+            DltValue::Float32(v) => w.u32(v.to_bits()),
+            DltValue::Float64(v) => w.u64(v.to_bits()),
+            DltValue::Float128(bits) => w.bytes(&bits),
+            _ => unreachable!(),
+        }
+        Ok(w.finish())
+    }
+
+    // Decode one argument, returning it and the number of bytes consumed
+    pub fn from_bytes(bytes: &[u8], big_endian: bool) -> Result<(Self, usize), DltParseError> {
+        let mut r = PayloadReader::new(bytes, big_endian);
+        let type_info = r.u32()?;
+        let arg = Self::read_value(&mut r, type_info)?;
+        Ok((arg, r.pos))
+    }
+
+    // Decode one packed argument whose type info is known out of band (non-verbose mode)
+    pub fn from_bytes_with_type_info(bytes: &[u8], type_info: u32, big_endian: bool) -> Result<(Self, usize), DltParseError> {
+        let mut r = PayloadReader::new(bytes, big_endian);
+        let arg = Self::read_value(&mut r, type_info)?;
+        Ok((arg, r.pos))
+    }
+
+    // Encode the value without its type info field (non-verbose mode)
+    pub fn to_packed_bytes(&self, big_endian: bool) -> Result<Vec<u8>, DltEncodeError> {
+        let bare = DltArgument::new(self.value.clone()).with_coding(self.coding);
+        Ok(bare.to_bytes(big_endian)?[4..].to_vec())
+    }
+
+    fn read_value(r: &mut PayloadReader, type_info: u32) -> Result<Self, DltParseError> {
+        let tyle = type_info & DLT_TYPE_INFO_TYLE;
+        let vari = type_info & DLT_TYPE_INFO_VARI != 0;
+        let coding = Coding::from_scod(type_info);
+        let unknown = DltParseError::UnknownArgumentType { offset: 0, type_info };
+
+        if type_info & (DLT_TYPE_INFO_ARAY | DLT_TYPE_INFO_STRU) != 0 {
+            return Err(unknown);
+        }
+
+        let mut arg = DltArgument {
+            value: DltValue::Bool(false),
+            name: None,
+            unit: None,
+            fixed_point: None,
+            coding,
+        };
+
+        if type_info & (DLT_TYPE_INFO_STRG | DLT_TYPE_INFO_RAWD | DLT_TYPE_INFO_TRAI) != 0 {
+            let len = r.u16()? as usize;
+            if vari {
+                let name_len = r.u16()? as usize;
+                arg.name = Some(cstr(r.take(name_len)?));
+            }
+            let data = r.take(len)?;
+            arg.value = if type_info & DLT_TYPE_INFO_RAWD != 0 {
+                DltValue::Raw(data.to_vec())
+            } else if type_info & DLT_TYPE_INFO_STRG != 0 {
+                DltValue::String(cstr(data))
+            } else {
+                DltValue::Trace(cstr(data))
+            };
+            return Ok(arg);
+        }
+
+        if type_info & DLT_TYPE_INFO_BOOL != 0 {
+            if vari {
+                let name_len = r.u16()? as usize;
+                arg.name = Some(cstr(r.take(name_len)?));
+            }
+            arg.value = DltValue::Bool(r.u8()? != 0);
+            return Ok(arg);
+        }
+
+        let numeric = DLT_TYPE_INFO_SINT | DLT_TYPE_INFO_UINT | DLT_TYPE_INFO_FLOA;
+        if type_info & numeric == 0 {
+            return Err(unknown);
+        }
+
+        if vari {
+            let name_len = r.u16()? as usize;
+            let unit_len = r.u16()? as usize;
+            arg.name = Some(cstr(r.take(name_len)?));
+            arg.unit = Some(cstr(r.take(unit_len)?));
+        }
+
+        let integer = type_info & (DLT_TYPE_INFO_SINT | DLT_TYPE_INFO_UINT) != 0;
+        if integer && type_info & DLT_TYPE_INFO_FIXP != 0 {
+            let quantization = f32::from_bits(r.u32()?);
+            let offset = match tyle {
+                DLT_TYLE_64BIT => r.u64()? as i64,
+                DLT_TYLE_128BIT => r.u128()? as i128 as i64,
+                _ => r.u32()? as i32 as i64,
+            };
+            arg.fixed_point = Some(FixedPoint { quantization, offset });
+        }
+
+        arg.value = if type_info & DLT_TYPE_INFO_SINT != 0 {
+            match tyle {
+                DLT_TYLE_8BIT => DltValue::Int8(r.u8()? as i8),
+                DLT_TYLE_16BIT => DltValue::Int16(r.u16()? as i16),
+                DLT_TYLE_32BIT => DltValue::Int32(r.u32()? as i32),
+                DLT_TYLE_64BIT => DltValue::Int64(r.u64()? as i64),
+                DLT_TYLE_128BIT => DltValue::Int128(r.u128()? as i128),
+                _ => return Err(unknown),
+            }
+        } else if type_info & DLT_TYPE_INFO_UINT != 0 {
+            match tyle {
+                DLT_TYLE_8BIT => DltValue::UInt8(r.u8()?),
+                DLT_TYLE_16BIT => DltValue::UInt16(r.u16()?),
+                DLT_TYLE_32BIT => DltValue::UInt32(r.u32()?),
+                DLT_TYLE_64BIT => DltValue::UInt64(r.u64()?),
+                DLT_TYLE_128BIT => DltValue::UInt128(r.u128()?),
+                _ => return Err(unknown),
+            }
+        } else {
+            match tyle {
+                DLT_TYLE_16BIT => DltValue::Float16(r.u16()?),
+                DLT_TYLE_32BIT => DltValue::Float32(f32::from_bits(r.u32()?)),
+                DLT_TYLE_64BIT => DltValue::Float64(f64::from_bits(r.u64()?)),
+                DLT_TYLE_128BIT => {
+                    let mut bits = [0u8; 16];
+                    bits.copy_from_slice(r.take(16)?);
+                    DltValue::Float128(bits)
+                }
+                _ => return Err(unknown),
+            }
+        };
+
+        Ok(arg)
+    }
+}
+
+impl fmt::Display for DltArgument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.coding, &self.value) {
+            (Coding::Hex, DltValue::UInt8(v)) => write!(f, "0x{:02x}", v),
+            (Coding::Hex, DltValue::UInt16(v)) => write!(f, "0x{:04x}", v),
+            (Coding::Hex, DltValue::UInt32(v)) => write!(f, "0x{:08x}", v),
+            (Coding::Hex, DltValue::UInt64(v)) => write!(f, "0x{:016x}", v),
+            (Coding::Hex, DltValue::UInt128(v)) => write!(f, "0x{:032x}", v),
+            (Coding::Bin, DltValue::UInt8(v)) => write!(f, "0b{:08b}", v),
+            (Coding::Bin, DltValue::UInt16(v)) => write!(f, "0b{:016b}", v),
+            _ => write!(f, "{}", self.value),
+        }
+    }
+}
+
+// Encode a list of arguments as a verbose payload; at most 255 arguments, as NOAR
+// is a single byte
+pub fn encode_arguments(args: &[DltArgument], big_endian: bool) -> Result<Vec<u8>, DltEncodeError> {
+    argument_count(args)?;
+    let mut payload = Vec::new();
+    for arg in args {
+        payload.extend_from_slice(&arg.to_bytes(big_endian)?);
+    }
+    Ok(payload)
+}
+
+// NOAR for an argument list
+pub fn argument_count(args: &[DltArgument]) -> Result<u8, DltEncodeError> {
+    u8::try_from(args.len()).map_err(|_| DltEncodeError::TooManyArguments { count: args.len() })
+}
+
+// 16-bit length field of a string, raw value, name or unit
+fn field_len(len: usize) -> Result<u16, DltEncodeError> {
+    u16::try_from(len).map_err(|_| DltEncodeError::ArgumentTooLong { len, max: u16::MAX as usize })
+}
+
+// Decode `noar` arguments from a verbose payload
+pub fn decode_arguments(payload: &[u8], noar: u8, big_endian: bool) -> Result<Vec<DltArgument>, DltParseError> {
+    let mut args = Vec::with_capacity(noar as usize);
+    let mut offset = 0;
+    for _ in 0..noar {
+        let (arg, used) = DltArgument::from_bytes(&payload[offset..], big_endian)
+            .map_err(|e| e.rebase(offset))?;
+        args.push(arg);
+        offset += used;
+    }
+    Ok(args)
+}
+
+// Strip the trailing null terminator(s) from a DLT string field
+fn cstr(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string()
+}
+
+// Endian-aware cursor over a payload
+pub(crate) struct PayloadReader<'a> {
+    bytes: &'a [u8],
+    pub(crate) pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> PayloadReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], big_endian: bool) -> Self {
+        PayloadReader { bytes, pos: 0, big_endian }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], DltParseError> {
+        DltParseError::check_len(self.bytes, self.pos, len)?;
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DltParseError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    // 4-byte identifier (APID, CTID, ECU ID, com interface)
+    pub(crate) fn id(&mut self) -> Result<[u8; 4], DltParseError> {
+        self.array()
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, DltParseError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, DltParseError> {
+        let b = self.array()?;
+        Ok(if self.big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) })
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, DltParseError> {
+        let b = self.array()?;
+        Ok(if self.big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, DltParseError> {
+        let b = self.array()?;
+        Ok(if self.big_endian { u64::from_be_bytes(b) } else { u64::from_le_bytes(b) })
+    }
+
+    pub(crate) fn u128(&mut self) -> Result<u128, DltParseError> {
+        let b = self.array()?;
+        Ok(if self.big_endian { u128::from_be_bytes(b) } else { u128::from_le_bytes(b) })
+    }
+}
+
+// Endian-aware payload builder
+pub(crate) struct PayloadWriter {
+    bytes: Vec<u8>,
+    big_endian: bool,
+}
+
+impl PayloadWriter {
+    pub(crate) fn new(big_endian: bool) -> Self {
+        PayloadWriter { bytes: Vec::new(), big_endian }
+    }
+
+    pub(crate) fn bytes(&mut self, data: &[u8]) {
+        self.bytes.extend_from_slice(data);
+    }
+
+    pub(crate) fn u16(&mut self, v: u16) {
+        let b = if self.big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+        self.bytes.extend_from_slice(&b);
+    }
+
+    pub(crate) fn u32(&mut self, v: u32) {
+        let b = if self.big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+        self.bytes.extend_from_slice(&b);
+    }
+
+    pub(crate) fn u64(&mut self, v: u64) {
+        let b = if self.big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+        self.bytes.extend_from_slice(&b);
+    }
+
+    pub(crate) fn u128(&mut self, v: u128) {
+        let b = if self.big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+        self.bytes.extend_from_slice(&b);
+    }
+
+    // u16 length (including null terminator) followed by the null-terminated string
+    fn cstr_with_len(&mut self, s: &str) -> Result<(), DltEncodeError> {
+        self.u16(field_len(s.len() + 1)?);
+        self.bytes(s.as_bytes());
+        self.bytes(&[0]);
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
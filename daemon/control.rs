@@ -0,0 +1,153 @@
+// Control requests of TCP clients (dlt-control)
+// The user library has no control channel, so log levels are applied by the daemon:
+// messages above the level of their context (or the default level) are dropped
+// before they are stored or forwarded. Contexts are known from their messages and
+// from requests naming them. Requests without a meaning here are answered with
+// "not supported".
+use dlt_core::{
+    AppId, AppLogInfo, ContextId, ContextLogInfo, ControlRequest, ControlResponse, ControlStatus, DltIdError, DltMessageRef,
+    DltMessageV2, MessageInfo, MessageKind, DLT_COM_INTERFACE_REMOTE, DLT_GET_LOG_INFO_IDS, DLT_GET_LOG_INFO_NO_MATCH, DLT_GET_LOG_INFO_OVERFLOW,
+    DLT_GET_LOG_INFO_WITH_DESCRIPTIONS, DLT_LOG_LEVEL_DEFAULT, DLT_LOG_LEVEL_OFF, DLT_SERVICE_ID_GET_LOG_INFO,
+    DLT_STANDARD_HEADER_MAX_SIZE,
+};
+use std::collections::BTreeMap;
+
+// Verbose: everything is forwarded until a client lowers a level
+const DLT_DAEMON_DEFAULT_LOG_LEVEL: i8 = 6;
+
+pub struct DltDaemonControl {
+    default_log_level: i8,
+    // Contexts per application, with their own level if one was set
+    apps: BTreeMap<AppId, BTreeMap<ContextId, Option<i8>>>,
+}
+
+impl DltDaemonControl {
+    pub fn new() -> Self {
+        DltDaemonControl { default_log_level: DLT_DAEMON_DEFAULT_LOG_LEVEL, apps: BTreeMap::new() }
+    }
+
+    // Record the message's context; returns false if its log level is filtered out
+    pub fn admit(&mut self, msg: &DltMessageRef) -> bool {
+        self.admit_ids(msg.apid(), msg.ctid(), msg.message_info())
+    }
+
+    // Fails for IDs longer than 4 bytes, which no log level can be set for
+    pub fn admit_v2(&mut self, msg: &DltMessageV2) -> Result<bool, DltIdError> {
+        let apid = msg.apid.as_deref().map(AppId::try_new).transpose()?;
+        let ctid = msg.ctid.as_deref().map(ContextId::try_new).transpose()?;
+        Ok(self.admit_ids(apid, ctid, Some(msg.msin)))
+    }
+
+    // Messages without IDs and messages other than logs always pass
+    fn admit_ids(&mut self, apid: Option<AppId>, ctid: Option<ContextId>, info: Option<MessageInfo>) -> bool {
+        let (Some(apid), Some(ctid)) = (apid, ctid) else {
+            return true;
+        };
+        let level = *self.apps.entry(apid).or_default().entry(ctid).or_default();
+        match info.map(|info| info.kind) {
+            Some(MessageKind::Log(msg_level)) => msg_level as i8 <= level.unwrap_or(self.default_log_level),
+            _ => true,
+        }
+    }
+
+    pub fn handle(&mut self, request: &ControlRequest) -> ControlResponse {
+        let status = |status| ControlResponse::Status { service_id: request.service_id(), status };
+        match request {
+            ControlRequest::SetLogLevel { apid, ctid, log_level, .. } => {
+                status(self.set_log_level(*apid, *ctid, *log_level))
+            }
+            ControlRequest::SetDefaultLogLevel { log_level, .. } => {
+                if !(DLT_LOG_LEVEL_OFF..=DLT_DAEMON_DEFAULT_LOG_LEVEL).contains(log_level) {
+                    return status(ControlStatus::Error);
+                }
+                self.default_log_level = *log_level;
+                status(ControlStatus::Ok)
+            }
+            ControlRequest::GetDefaultLogLevel => {
+                ControlResponse::GetDefaultLogLevel { status: ControlStatus::Ok, log_level: self.default_log_level }
+            }
+            ControlRequest::GetLogInfo { options, apid, ctid, .. } => self.get_log_info(*options, *apid, *ctid),
+            ControlRequest::GetSoftwareVersion => ControlResponse::GetSoftwareVersion {
+                status: ControlStatus::Ok,
+                version: format!("dlt-daemon {}", env!("CARGO_PKG_VERSION")),
+            },
+            ControlRequest::ResetToFactoryDefault => {
+                self.default_log_level = DLT_DAEMON_DEFAULT_LOG_LEVEL;
+                self.apps.values_mut().flat_map(|contexts| contexts.values_mut()).for_each(|level| *level = None);
+                status(ControlStatus::Ok)
+            }
+            ControlRequest::Marker => status(ControlStatus::Ok),
+            _ => status(ControlStatus::NotSupported),
+        }
+    }
+
+    // An empty application or context ID selects all of them; -1 returns the
+    // contexts to the default level
+    fn set_log_level(&mut self, apid: AppId, ctid: ContextId, log_level: i8) -> ControlStatus {
+        if !(DLT_LOG_LEVEL_DEFAULT..=DLT_DAEMON_DEFAULT_LOG_LEVEL).contains(&log_level) {
+            return ControlStatus::Error;
+        }
+        let level = (log_level != DLT_LOG_LEVEL_DEFAULT).then_some(log_level);
+        if !is_wildcard(apid.0) && !is_wildcard(ctid.0) {
+            self.apps.entry(apid).or_default().insert(ctid, level);
+            return ControlStatus::Ok;
+        }
+        let mut matched = false;
+        for (_, contexts) in self.apps.iter_mut().filter(|(id, _)| id_matches(apid.0, id.0)) {
+            for (_, context_level) in contexts.iter_mut().filter(|(id, _)| id_matches(ctid.0, id.0)) {
+                *context_level = level;
+                matched = true;
+            }
+        }
+        if matched { ControlStatus::Ok } else { ControlStatus::Error }
+    }
+
+    fn get_log_info(&self, options: u8, apid: AppId, ctid: ContextId) -> ControlResponse {
+        let response = |status, apps| ControlResponse::GetLogInfo {
+            status: ControlStatus::Other(status),
+            apps,
+            com_interface: DLT_COM_INTERFACE_REMOTE,
+        };
+        if !(DLT_GET_LOG_INFO_IDS..=DLT_GET_LOG_INFO_WITH_DESCRIPTIONS).contains(&options) {
+            let status = ControlStatus::Error;
+            return ControlResponse::Status { service_id: DLT_SERVICE_ID_GET_LOG_INFO, status };
+        }
+        let apps: Vec<AppLogInfo> = self
+            .apps
+            .iter()
+            .filter(|(id, _)| id_matches(apid.0, id.0))
+            .map(|(id, contexts)| AppLogInfo {
+                apid: *id,
+                contexts: contexts
+                    .iter()
+                    .filter(|(id, _)| id_matches(ctid.0, id.0))
+                    .map(|(id, level)| ContextLogInfo {
+                        ctid: *id,
+                        log_level: Some(level.unwrap_or(DLT_LOG_LEVEL_DEFAULT)),
+                        trace_status: None,
+                        description: None,
+                    })
+                    .collect(),
+                description: None,
+            })
+            .filter(|app| !app.contexts.is_empty())
+            .collect();
+        if apps.is_empty() {
+            return response(DLT_GET_LOG_INFO_NO_MATCH, Vec::new());
+        }
+        let info = response(options, apps);
+        // Has to fit one control message
+        match info.to_bytes(false) {
+            Ok(bytes) if DLT_STANDARD_HEADER_MAX_SIZE + 10 + bytes.len() <= u16::MAX as usize => info,
+            _ => response(DLT_GET_LOG_INFO_OVERFLOW, Vec::new()),
+        }
+    }
+}
+
+fn is_wildcard(id: [u8; 4]) -> bool {
+    id == [0; 4]
+}
+
+fn id_matches(pattern: [u8; 4], id: [u8; 4]) -> bool {
+    is_wildcard(pattern) || pattern == id
+}
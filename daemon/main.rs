@@ -1,22 +1,177 @@
 // daemon: DLT daemon with lockless buffer (decoupled design)
 use std::os::unix::net::UnixListener;
 use std::net::TcpListener;
-use std::io::{Read, Write};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use dlt_buffer::LocklessBuffer;
+use std::time::Duration;
+use dlt_buffer::{BroadcastBuffer, BroadcastReceiver, HistoryRing, LocklessBuffer, SlowConsumerPolicy};
+use dlt_core::{
+    AppId, ContextId, ControlResponse, ControlStatus, DltCounterGap, DltCounterTracker, DltEncodeError, DltFrame,
+    DltFrameDecoder, DltMessage, DltMessageRef, DltMessageV2, DltParseError, DltParseErrorStats, EcuId,
+    DLT_SERVICE_ID_MARKER,
+};
+use dlt_storage::DltLogStorage;
+use nix::sys::signal::{SigSet, Signal};
+use dlt_transport::{SerialTransport, Transport, DLT_SERIAL_DEFAULT_BAUDRATE};
+
+mod control;
+use control::DltDaemonControl;
 
 const DLT_DAEMON_SOCKET: &str = "/tmp/dlt";
 const DLT_DAEMON_PORT: &str = "127.0.0.1:3490";
+// Messages queued per TCP client before the slow-client policy applies
+const DLT_DAEMON_CLIENT_QUEUE_SIZE: usize = 1024;
+// How often a client blocked in write checks whether it was disconnected
+const DLT_DAEMON_CLIENT_WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+// Bytes of recent messages replayed to each new TCP client (COVESA's startup ring buffer)
+const DLT_DAEMON_HISTORY_SIZE: usize = 500_000;
+// Storage header ECU ID for serial messages without one in the standard header,
+// also the ECU of the daemon's own messages
+const DLT_DAEMON_ECU_ID: EcuId = EcuId::new_const("ECU1");
+const DLT_DAEMON_APP_ID: AppId = AppId::new_const("DA1");
+const DLT_DAEMON_CONTEXT_ID: ContextId = ContextId::new_const("DC1");
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let mut serial_device: Option<String> = None;
+    let mut baudrate = DLT_SERIAL_DEFAULT_BAUDRATE;
+    let mut storage_dir: Option<String> = None;
+    let mut client_queue_size = DLT_DAEMON_CLIENT_QUEUE_SIZE;
+    let mut slow_client_policy = SlowConsumerPolicy::default();
+    let mut history_size = DLT_DAEMON_HISTORY_SIZE;
+
+    // Parse arguments: -s <tty> -b <baudrate> -l <logstorage dir> -q <messages>
+    //                  -Q <drop-oldest|disconnect> -r <history bytes>
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-s" if i + 1 < args.len() => {
+                serial_device = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-b" if i + 1 < args.len() => {
+                baudrate = args[i + 1].parse().unwrap_or_else(|e| {
+                    eprintln!("Invalid baud rate '{}': {}", args[i + 1], e);
+                    std::process::exit(1);
+                });
+                i += 2;
+            }
+            "-l" if i + 1 < args.len() => {
+                storage_dir = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-q" if i + 1 < args.len() => {
+                client_queue_size = match args[i + 1].parse() {
+                    Ok(size) if size > 0 => size,
+                    _ => {
+                        eprintln!("Invalid client queue size '{}'", args[i + 1]);
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "-r" if i + 1 < args.len() => {
+                history_size = args[i + 1].parse().unwrap_or_else(|_| {
+                    eprintln!("Invalid history size '{}'", args[i + 1]);
+                    std::process::exit(1);
+                });
+                i += 2;
+            }
+            "-Q" if i + 1 < args.len() => {
+                slow_client_policy = match args[i + 1].as_str() {
+                    "drop-oldest" => SlowConsumerPolicy::DropOldest,
+                    "disconnect" => SlowConsumerPolicy::Disconnect,
+                    policy => {
+                        eprintln!("Invalid slow client policy '{}' (drop-oldest or disconnect)", policy);
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            _ => {
+                i += 1;
+            }
+        }
+    }
+
+    // Offline log storage, written whether or not a client is connected
+    let storage = storage_dir.map(|dir| match DltLogStorage::open(&dir) {
+        Ok(storage) => {
+            println!("DLT daemon storing logs in {} ({} filters)", dir, storage.filters().count());
+            Arc::new(Mutex::new(storage))
+        }
+        Err(e) => {
+            eprintln!("Failed to open log storage {}: {}", dir, e);
+            std::process::exit(1);
+        }
+    });
+
+    // Malformed input of all user connections and the serial input, dropped before queueing
+    let input_errors = Arc::new(Mutex::new(DltParseErrorStats::default()));
+    // Messages of all inputs dropped because the input buffer was full
+    let input_dropped = Arc::new(AtomicU64::new(0));
+
+    // SIGINT/SIGTERM are taken by a dedicated thread so files are synced on exit
+    // (blocked here, before any thread is spawned, so every thread inherits the mask)
+    let mut signals = SigSet::empty();
+    signals.add(Signal::SIGINT);
+    signals.add(Signal::SIGTERM);
+    signals.thread_block().expect("Failed to block signals");
+    let storage_for_exit = storage.clone();
+    let input_errors_for_exit = Arc::clone(&input_errors);
+    let input_dropped_for_exit = Arc::clone(&input_dropped);
+    thread::spawn(move || {
+        let signal = signals.wait().expect("Failed to wait for signals");
+        // Copied, so no lock is held while waiting for storage below
+        let input_errors = input_errors_for_exit.lock().unwrap().clone();
+        if input_errors.total() > 0 {
+            eprintln!("Malformed input dropped: {}", input_errors);
+        }
+        let input_dropped = input_dropped_for_exit.load(Ordering::Relaxed);
+        if input_dropped > 0 {
+            eprintln!("Messages dropped on a full input buffer: {}", input_dropped);
+        }
+        if let Some(storage) = storage_for_exit {
+            if let Err(e) = storage.lock().unwrap().sync() {
+                eprintln!("Failed to sync log storage: {}", e);
+            }
+        }
+        let _ = std::fs::remove_file(DLT_DAEMON_SOCKET);
+        println!("DLT daemon exiting on {}", signal);
+        std::process::exit(0);
+    });
+
     // Remove old socket if exists
     let _ = std::fs::remove_file(DLT_DAEMON_SOCKET);
 
     // Create lockless buffer for logs
     let log_buffer: LocklessBuffer<Vec<u8>> = LocklessBuffer::new(1024);
 
+    // Message counters per ECU, application and context, shared by all inputs
+    // (one context may write through several user connections, one per user buffer)
+    let counters = Arc::new(Mutex::new(DltCounterTracker::new()));
+    // Late messages of a context can still come through another open connection, so
+    // held-back gaps are only confirmed once the last user connection is closed
+    let user_connections = Arc::new(AtomicUsize::new(0));
+
+    // Log levels set by clients, applied to every input
+    let control = Arc::new(Mutex::new(DltDaemonControl::new()));
+
+    let input = DaemonInput {
+        buffer: log_buffer.clone(),
+        counters: Arc::clone(&counters),
+        control: Arc::clone(&control),
+        storage: storage.clone(),
+        errors: Arc::clone(&input_errors),
+        dropped: Arc::clone(&input_dropped),
+    };
+
     // Spawn Unix socket listener (receives logs from users)
-    let buffer_for_unix = log_buffer.clone();
+    let input_for_unix = input.clone();
     thread::spawn(move || {
         let listener = UnixListener::bind(DLT_DAEMON_SOCKET).unwrap();
         println!("DLT daemon listening on {}", DLT_DAEMON_SOCKET);
@@ -27,44 +182,384 @@ fn main() {
                 Err(_) => continue,
             };
 
-            let buffer = buffer_for_unix.clone();
+            let input = input_for_unix.clone();
+            let user_connections = Arc::clone(&user_connections);
+            user_connections.fetch_add(1, Ordering::SeqCst);
             thread::spawn(move || {
                 let mut buf = vec![0u8; 4096];
+                // Validates the stream; messages split across reads are reassembled here
+                let mut decoder = DltFrameDecoder::new();
+                let mut errors = DltParseErrorStats::default();
+                let mut dropped = 0u64;
                 loop {
                     match stream.read(&mut buf) {
                         Ok(0) => break, // Connection closed
                         Ok(n) => {
-                            let msg = buf[..n].to_vec();
-                            let _ = buffer.push(msg);
+                            decoder.push(&buf[..n]);
+                            // Only whole, valid messages are queued, one entry each,
+                            // however the user's writes were split or batched. Locks
+                            // are taken per message, but the storage lock is shared by
+                            // all inputs, so a slow storage write (or archiving a
+                            // rotated file) holds back every connection and the serial input.
+                            while let Some(frame) = decoder.next_frame() {
+                                match ingest_frame(&input, frame, "user") {
+                                    Ingested::Skipped(reason) => errors.record(&reason),
+                                    Ingested::BufferFull => dropped += 1,
+                                    Ingested::Queued | Ingested::Discarded => {}
+                                }
+                            }
                         }
                         Err(_) => break,
                     }
                 }
+                // Garbage or a message cut off by the disconnect
+                if let Some(skipped) = decoder.finish() {
+                    errors.record(&skipped.reason);
+                    input.errors.lock().unwrap().record(&skipped.reason);
+                    eprintln!("Malformed input from user ({}): {}", skipped.reason.kind(), skipped);
+                }
+                if errors.total() > 0 {
+                    eprintln!("User connection closed with parse errors: {}, {} bytes skipped",
+                        errors, decoder.skipped_bytes());
+                }
+                if dropped > 0 {
+                    eprintln!("User connection closed, {} messages dropped on a full input buffer", dropped);
+                }
+                let mut counters = input.counters.lock().unwrap();
+                if user_connections.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    print_gaps(counters.flush());
+                }
+                print_lost_messages(&counters);
             });
         }
     });
 
+    // Serial input (ECU UART): messages are re-framed with storage headers and forwarded
+    if let Some(device) = serial_device {
+        let input = input.clone();
+        thread::spawn(move || {
+            let mut serial = SerialTransport::new(&device, baudrate);
+            if let Err(e) = serial.connect() {
+                eprintln!("Failed to open serial device {}: {}", device, e);
+                return;
+            }
+            println!("DLT daemon reading serial input from {} at {} baud", device, baudrate);
+
+            let mut buf = vec![0u8; 4096];
+            let mut decoder = DltFrameDecoder::with_serial_header(DLT_DAEMON_ECU_ID);
+            let mut dropped = 0u64;
+            loop {
+                match serial.receive(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        decoder.push(&buf[..n]);
+                        while let Some(frame) = decoder.next_frame() {
+                            if let Ingested::BufferFull = ingest_frame(&input, frame, "serial") {
+                                dropped += 1;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Serial read error: {}", e);
+                        break;
+                    }
+                }
+            }
+            println!("Serial input {} closed ({} bytes skipped, {} messages dropped on a full input buffer)",
+                device, decoder.skipped_bytes(), dropped);
+        });
+    }
+
+    // Every client gets every message through its own queue, filled from the
+    // input buffer by one dispatcher thread. The dispatcher also keeps the recent
+    // messages for clients that connect later; history and subscription change
+    // under one lock, so a new client gets each message exactly once.
+    let clients: BroadcastBuffer<Arc<[u8]>> = BroadcastBuffer::new(client_queue_size, slow_client_policy);
+    let history: Arc<Mutex<HistoryRing<Arc<[u8]>>>> = Arc::new(Mutex::new(HistoryRing::new(history_size)));
+    let clients_for_dispatch = clients.clone();
+    let history_for_dispatch = Arc::clone(&history);
+    thread::spawn(move || {
+        loop {
+            if let Some(msg) = log_buffer.pop() {
+                let msg: Arc<[u8]> = Arc::from(msg);
+                let mut history = history_for_dispatch.lock().unwrap();
+                history.push(Arc::clone(&msg));
+                clients_for_dispatch.publish(msg);
+            } else {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    });
+
     // TCP listener for clients (dlt-receive)
     let listener = TcpListener::bind(DLT_DAEMON_PORT).unwrap();
     println!("DLT daemon serving on {}", DLT_DAEMON_PORT);
+    if history_size > 0 {
+        println!("DLT daemon replaying up to {} bytes of history to new clients", history_size);
+    }
 
     for stream in listener.incoming() {
-        let mut stream = match stream {
+        let stream = match stream {
             Ok(s) => s,
             Err(_) => continue,
         };
 
-        let buffer = log_buffer.clone();
+        let (replay, receiver) = {
+            let history = history.lock().unwrap();
+            (history.iter().cloned().collect::<Vec<_>>(), clients.subscribe())
+        };
+        let control = Arc::clone(&control);
         thread::spawn(move || {
-            loop {
-                if let Some(log_msg) = buffer.pop() {
-                    if stream.write_all(&log_msg).is_err() {
-                        break;
+            let peer = stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_default();
+            serve_client(stream, replay, history_size > 0, &receiver, control);
+            if receiver.is_disconnected() {
+                eprintln!("Client {} disconnected: too slow ({} messages queued)", peer, client_queue_size);
+            } else if receiver.dropped() > 0 {
+                eprintln!("Client {} closed, {} messages dropped while it was too slow", peer, receiver.dropped());
+            }
+        });
+    }
+}
+
+// Send the history, a marker message (with history enabled) and then the client's
+// queue until it closes the connection or the slow-client policy disconnects it.
+// Control requests are read by a second thread; their responses go out between
+// whole messages.
+fn serve_client(
+    mut stream: TcpStream,
+    replay: Vec<Arc<[u8]>>,
+    marker: bool,
+    receiver: &BroadcastReceiver<Arc<[u8]>>,
+    control: Arc<Mutex<DltDaemonControl>>,
+) {
+    // Writes blocked by a stalled client return now and then to check for disconnect
+    if stream.set_write_timeout(Some(DLT_DAEMON_CLIENT_WRITE_TIMEOUT)).is_err() {
+        return;
+    }
+    let Ok(request_stream) = stream.try_clone() else {
+        return;
+    };
+    let (response_sender, responses) = mpsc::channel();
+    thread::spawn(move || read_control_requests(request_stream, &control, &response_sender));
+
+    serve_messages(&mut stream, replay, marker, receiver, &responses);
+    // Ends the request reader as well
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn serve_messages(
+    stream: &mut TcpStream,
+    replay: Vec<Arc<[u8]>>,
+    marker: bool,
+    receiver: &BroadcastReceiver<Arc<[u8]>>,
+    responses: &Receiver<Vec<u8>>,
+) {
+    for log_msg in replay {
+        if !send_responses(stream, responses, receiver) || !send_to_client(stream, &log_msg, receiver) {
+            return;
+        }
+    }
+    if marker {
+        let marker = ControlResponse::Status { service_id: DLT_SERVICE_ID_MARKER, status: ControlStatus::Ok };
+        let msg = control_message(&marker).expect("marker fits a message");
+        if !send_to_client(stream, &msg.to_bytes(), receiver) {
+            return;
+        }
+    }
+    loop {
+        if receiver.is_disconnected() || !send_responses(stream, responses, receiver) {
+            return;
+        }
+        let Some(log_msg) = receiver.pop() else {
+            thread::sleep(Duration::from_millis(10));
+            continue;
+        };
+        if !send_to_client(stream, &log_msg, receiver) {
+            return;
+        }
+    }
+}
+
+// Answer each control request of a client until it closes the connection;
+// other messages from clients are ignored
+fn read_control_requests(mut stream: TcpStream, control: &Mutex<DltDaemonControl>, responses: &Sender<Vec<u8>>) {
+    let mut buf = vec![0u8; 4096];
+    let mut decoder = DltFrameDecoder::new();
+    loop {
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => decoder.push(&buf[..n]),
+        }
+        while let Some(frame) = decoder.next_frame() {
+            let DltFrame::Message(msg) = frame else {
+                continue;
+            };
+            let response = match msg.to_message().control_request() {
+                Some(Ok(request)) => {
+                    let response = control.lock().unwrap().handle(&request);
+                    println!("Control request {}: {}", request, response);
+                    response
+                }
+                Some(Err(DltParseError::UnknownServiceId { service_id, .. })) => {
+                    ControlResponse::Status { service_id, status: ControlStatus::NotSupported }
+                }
+                Some(Err(e)) => {
+                    eprintln!("Malformed control request: {}", e);
+                    continue;
+                }
+                None => continue,
+            };
+            match control_message(&response) {
+                Ok(msg) => {
+                    if responses.send(msg.to_bytes()).is_err() {
+                        return;
                     }
-                } else {
-                    thread::sleep(std::time::Duration::from_millis(10));
                 }
+                Err(e) => eprintln!("Failed to encode control response: {}", e),
             }
-        });
+        }
+    }
+}
+
+// Control response sent by the daemon itself
+fn control_message(response: &ControlResponse) -> Result<DltMessage, DltEncodeError> {
+    DltMessage::new_control_response(DLT_DAEMON_ECU_ID, DLT_DAEMON_APP_ID, DLT_DAEMON_CONTEXT_ID, response)
+}
+
+// Responses queued so far, or false once the client is gone
+fn send_responses(
+    stream: &mut TcpStream,
+    responses: &Receiver<Vec<u8>>,
+    receiver: &BroadcastReceiver<Arc<[u8]>>,
+) -> bool {
+    while let Ok(response) = responses.try_recv() {
+        if !send_to_client(stream, &response, receiver) {
+            return false;
+        }
+    }
+    true
+}
+
+// Whole message, or false once the client is gone or disconnected
+fn send_to_client(stream: &mut TcpStream, msg: &[u8], receiver: &BroadcastReceiver<Arc<[u8]>>) -> bool {
+    let mut written = 0;
+    while written < msg.len() {
+        match stream.write(&msg[written..]) {
+            Ok(0) => return false,
+            Ok(n) => written += n,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => {
+                if receiver.is_disconnected() {
+                    return false;
+                }
+            }
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+// State shared by all inputs (user connections, serial)
+#[derive(Clone)]
+struct DaemonInput {
+    buffer: LocklessBuffer<Vec<u8>>,
+    counters: Arc<Mutex<DltCounterTracker>>,
+    control: Arc<Mutex<DltDaemonControl>>,
+    storage: Option<Arc<Mutex<DltLogStorage>>>,
+    errors: Arc<Mutex<DltParseErrorStats>>, // Malformed input of all inputs
+    dropped: Arc<AtomicU64>,                // Messages of all inputs lost on a full buffer
+}
+
+// What became of one input frame
+enum Ingested {
+    Queued,
+    // Filtered by log level or not encodable
+    Discarded,
+    // Stored, but lost for the clients: the input buffer was full
+    BufferFull,
+    // Bytes that did not start a message, and why
+    Skipped(DltParseError),
+}
+
+// Check, filter and store one frame of an input, then queue it for the clients
+fn ingest_frame(input: &DaemonInput, frame: DltFrame, source: &str) -> Ingested {
+    let msg = match frame {
+        DltFrame::Message(msg) => {
+            check_counter(&mut input.counters.lock().unwrap(), &msg);
+            if !input.control.lock().unwrap().admit(&msg) {
+                return Ingested::Discarded;
+            }
+            if let Some(storage) = &input.storage {
+                store(&mut storage.lock().unwrap(), &msg);
+            }
+            msg.as_bytes().to_vec()
+        }
+        DltFrame::MessageV2(msg) => {
+            print_gaps(input.counters.lock().unwrap().observe_message_v2(&msg));
+            let admitted = input.control.lock().unwrap().admit_v2(&msg);
+            match admitted {
+                Ok(true) => {}
+                Ok(false) => return Ingested::Discarded,
+                Err(e) => {
+                    eprintln!("Dropped version 2 message from {}: {}", source, e);
+                    return Ingested::Discarded;
+                }
+            }
+            if let Some(storage) = &input.storage {
+                store_v2(&mut storage.lock().unwrap(), &msg);
+            }
+            match msg.to_bytes() {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    eprintln!("Dropped version 2 message from {}: {}", source, e);
+                    return Ingested::Discarded;
+                }
+            }
+        }
+        DltFrame::Skipped(skipped) => {
+            input.errors.lock().unwrap().record(&skipped.reason);
+            eprintln!("Malformed input from {} ({}): {}", source, skipped.reason.kind(), skipped);
+            return Ingested::Skipped(skipped.reason);
+        }
+    };
+    if input.buffer.push(msg).is_err() {
+        input.dropped.fetch_add(1, Ordering::Relaxed);
+        return Ingested::BufferFull;
+    }
+    Ingested::Queued
+}
+
+fn check_counter(counters: &mut DltCounterTracker, msg: &DltMessageRef) {
+    print_gaps(counters.observe_message(msg));
+}
+
+fn print_gaps(gaps: Vec<DltCounterGap>) {
+    for gap in gaps {
+        eprintln!("Message counter gap: {}", gap);
+    }
+}
+
+// Write errors are reported and the message is dropped from storage only
+fn store(storage: &mut DltLogStorage, msg: &DltMessageRef) {
+    if let Err(e) = storage.store(msg) {
+        eprintln!("Log storage write error: {}", e);
+    }
+}
+
+fn store_v2(storage: &mut DltLogStorage, msg: &DltMessageV2) {
+    if let Err(e) = storage.store_v2(msg) {
+        eprintln!("Log storage write error: {}", e);
+    }
+}
+
+// Sources that lost messages so far
+fn print_lost_messages(counters: &DltCounterTracker) {
+    if counters.total_lost() == 0 {
+        return;
+    }
+    eprintln!("Lost messages: {}", counters.total_lost());
+    for ((ecu, apid, ctid), stats) in counters.sources() {
+        if stats.lost > 0 {
+            eprintln!("  {} {} {}: {}", ecu.as_str(), apid.as_str(), ctid.as_str(), stats);
+        }
     }
 }
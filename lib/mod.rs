@@ -8,13 +8,19 @@ pub use dlt_core::*;
 pub use dlt_transport::*;
 
 use crossbeam::channel::{Sender, Receiver, bounded, TrySendError};
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use std::env;
 use once_cell::sync::Lazy;
+use nix::sys::time::TimeSpec;
+use nix::time::{clock_gettime, ClockId};
 
 const DLT_DAEMON_SOCKET: &str = "/tmp/dlt";
+const DLT_USER_ECU_ID: EcuId = EcuId::new_const("ECU1");
+
+// Identifies the messages of one segmented network trace
+static NETWORK_TRACE_ID: AtomicU32 = AtomicU32::new(1);
 
 // Environment variables for configuration
 // DLT_USER_NUM_BUFFERS: Number of ring buffers (default: 4)
@@ -36,6 +42,19 @@ pub enum DltLogLevel {
     Verbose = 6,
 }
 
+impl From<DltLogLevel> for LogLevel {
+    fn from(level: DltLogLevel) -> Self {
+        match level {
+            DltLogLevel::Fatal => LogLevel::Fatal,
+            DltLogLevel::Error => LogLevel::Error,
+            DltLogLevel::Warn => LogLevel::Warn,
+            DltLogLevel::Info => LogLevel::Info,
+            DltLogLevel::Debug => LogLevel::Debug,
+            DltLogLevel::Verbose => LogLevel::Verbose,
+        }
+    }
+}
+
 /// Overflow handling mode for ring buffer
 /// Can be changed at runtime via dlt-control messages
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -181,7 +200,7 @@ impl DltUserState {
                 .spawn(move || {
                     Self::worker_thread(rx, i, batch_size, stats_clone);
                 })
-                .expect(&format!("Failed to spawn worker for buffer {}", i)));
+                .unwrap_or_else(|_| panic!("Failed to spawn worker for buffer {}", i)));
             
             senders.push(tx);
             stats.push(buffer_stats);
@@ -257,15 +276,11 @@ impl DltUserState {
             }
 
             // Retry connection if disconnected
-            if !connected {
-                if transport.connect().is_ok() {
-                    connected = true;
-                    // Retry sending batch
-                    if !message_bytes.is_empty() {
-                        if Self::writev_send(&mut transport, &message_bytes).is_ok() {
-                            stats.sent.fetch_add(message_bytes.len() as u64, Ordering::Relaxed);
-                        }
-                    }
+            if !connected && transport.connect().is_ok() {
+                connected = true;
+                // Retry sending batch
+                if !message_bytes.is_empty() && Self::writev_send(&mut transport, &message_bytes).is_ok() {
+                    stats.sent.fetch_add(message_bytes.len() as u64, Ordering::Relaxed);
                 }
             }
         }
@@ -307,7 +322,7 @@ impl DltUserState {
         // Simple hash-based distribution
         // Fatal logs go to buffer 0, others distributed
         match level {
-            DltLogLevel::Fatal if self.senders.len() > 0 => 0,
+            DltLogLevel::Fatal if !self.senders.is_empty() => 0,
             DltLogLevel::Error if self.senders.len() > 1 => 1 % self.senders.len(),
             _ => {
                 // Use a simple counter for round-robin
@@ -363,27 +378,48 @@ impl DltUserState {
 }
 
 // Global state singleton - initialized lazily and never dropped
-static DLT_USER: Lazy<DltUserState> = Lazy::new(|| DltUserState::new());
+static DLT_USER: Lazy<DltUserState> = Lazy::new(DltUserState::new);
 
 pub struct DltContext {
     app_id: AppId,
     ctx_id: ContextId,
     ecu_id: EcuId,
+    counter: AtomicU8, // Message counter (MCNT) of the next message, wraps at 255
 }
 
 impl DltContext {
-    /// Register a new DLT context (equivalent to DLT_REGISTER_APP + DLT_REGISTER_CONTEXT)
-    pub fn new(app_id: &str, ctx_id: &str, _app_desc: &str, _ctx_desc: &str) -> Self {
+    /// Register a new DLT context (equivalent to DLT_REGISTER_APP + DLT_REGISTER_CONTEXT),
+    /// failing with InvalidInput unless both IDs are 1-4 printable ASCII characters
+    pub fn new(app_id: &str, ctx_id: &str, _app_desc: &str, _ctx_desc: &str) -> std::io::Result<Self> {
+        let invalid = |what: &str, id: &str, e: DltIdError| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("invalid {} \"{}\": {}", what, id, e))
+        };
+        let app = app_id.parse().map_err(|e| invalid("application ID", app_id, e))?;
+        let ctx = ctx_id.parse().map_err(|e| invalid("context ID", ctx_id, e))?;
+        Ok(Self::from_ids(app, ctx))
+    }
+
+    /// Register a new DLT context from already validated IDs (e.g. AppId::new_const)
+    pub fn from_ids(app_id: AppId, ctx_id: ContextId) -> Self {
         // Ensure DLT_USER is initialized (just by accessing it)
         let _ = &*DLT_USER;
 
         DltContext {
-            app_id: AppId::new(app_id),
-            ctx_id: ContextId::new(ctx_id),
-            ecu_id: EcuId::new("ECU1"),
+            app_id,
+            ctx_id,
+            ecu_id: DLT_USER_ECU_ID,
+            counter: AtomicU8::new(0),
         }
     }
 
+    pub fn app_id(&self) -> AppId {
+        self.app_id
+    }
+
+    pub fn ctx_id(&self) -> ContextId {
+        self.ctx_id
+    }
+
     /// Log a message with specified log level (equivalent to DLT_LOG)
     /// This is completely lock-free and never blocks (unless using BlockWithTimeout mode)
     /// Messages are automatically routed to appropriate buffer based on log level
@@ -394,7 +430,54 @@ impl DltContext {
     /// Log to a specific buffer (allows manual buffer selection)
     pub fn log_to_buffer(&self, level: DltLogLevel, num: i32, message: &str, buffer_id: Option<usize>) -> std::io::Result<()> {
         let payload = format!("{} {}", num, message);
-        let msg = DltMessage::new_verbose(self.ecu_id, self.app_id, self.ctx_id, &payload);
+        let msg = DltMessage::new_verbose(self.ecu_id, self.app_id, self.ctx_id, &payload).map_err(encode_error)?;
+        self.enqueue(level, msg, buffer_id)
+    }
+
+    /// Log a verbose message carrying typed arguments (bool, integers, floats, strings, raw data)
+    pub fn log_args(&self, level: DltLogLevel, args: &[DltArgument]) -> std::io::Result<()> {
+        let msg = DltMessage::new_verbose_args(self.ecu_id, self.app_id, self.ctx_id, args).map_err(encode_error)?;
+        self.enqueue(level, msg, None)
+    }
+
+    /// Trace a network frame as header plus payload (equivalent to DLT_TRACE_NETWORK)
+    /// Frames too large for a single message are sent as a segmented sequence
+    pub fn trace_network(&self, trace_type: NetworkTraceType, header: &[u8], payload: &[u8]) -> std::io::Result<()> {
+        if !NetworkTrace::fits_single_message(header, payload) {
+            return self.trace_network_segmented(trace_type, header, payload);
+        }
+        let trace = NetworkTrace::Frame { header: header.to_vec(), payload: payload.to_vec() };
+        let msg = DltMessage::new_network_trace(self.ecu_id, self.app_id, self.ctx_id, trace_type, &trace)
+            .map_err(encode_error)?;
+        self.submit(DltLogLevel::Info, msg, None)
+    }
+
+    /// Trace a network frame as start, segment and end messages (equivalent to DLT_TRACE_NETWORK_SEGMENTED)
+    /// All messages go through the same buffer so they reach the daemon in order
+    pub fn trace_network_segmented(&self, trace_type: NetworkTraceType, header: &[u8], payload: &[u8]) -> std::io::Result<()> {
+        let id = NETWORK_TRACE_ID.fetch_add(1, Ordering::Relaxed);
+        let sequence = NetworkTrace::segmented(id, header, payload, DLT_MAX_TRACE_SEGMENT_SIZE).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Network trace payload too large")
+        })?;
+        let buffer = DLT_USER.select_buffer(DltLogLevel::Info);
+        for trace in &sequence {
+            let msg = DltMessage::new_network_trace(self.ecu_id, self.app_id, self.ctx_id, trace_type, trace)
+                .map_err(encode_error)?;
+            self.submit(DltLogLevel::Info, msg, Some(buffer))?;
+        }
+        Ok(())
+    }
+
+    fn enqueue(&self, level: DltLogLevel, msg: DltMessage, buffer_id: Option<usize>) -> std::io::Result<()> {
+        self.submit(level, msg.with_log_level(level.into()), buffer_id)
+    }
+
+    // Queue a finished message; `level` only selects the buffer
+    // Every message takes a counter value, so messages dropped on overflow show up as gaps
+    fn submit(&self, level: DltLogLevel, msg: DltMessage, buffer_id: Option<usize>) -> std::io::Result<()> {
+        // Stamped here, on the caller's thread, not when a worker sends the message
+        let mut msg = msg.with_timestamp(dlt_uptime()).map_err(encode_error)?;
+        msg.standard_header.mcnt = self.counter.fetch_add(1, Ordering::Relaxed);
 
         // Auto-select buffer based on log level if not specified
         let buffer = buffer_id.unwrap_or_else(|| DLT_USER.select_buffer(level));
@@ -411,7 +494,7 @@ impl DltContext {
         // Lock-free enqueue - never blocks unless timeout mode
         match DLT_USER.enqueue_message(envelope) {
             Ok(_) => Ok(()),
-            Err(e) => Err(std::io::Error::new(std::io::ErrorKind::Other, e)),
+            Err(e) => Err(std::io::Error::other(e)),
         }
     }
 
@@ -435,6 +518,22 @@ impl Drop for DltContext {
     }
 }
 
+// A message that cannot be encoded (argument or message too long) is the caller's input error
+fn encode_error(e: DltEncodeError) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+}
+
+/// Time since boot from CLOCK_MONOTONIC in 0.1 ms units, wrapping after about 5 days
+/// (equivalent to dlt_uptime). Unaffected by wall-clock adjustments and shared by all
+/// processes, so it orders messages across applications.
+pub fn dlt_uptime() -> u32 {
+    // Cannot fail for CLOCK_MONOTONIC
+    let ts = clock_gettime(ClockId::CLOCK_MONOTONIC).unwrap_or_else(|_| TimeSpec::new(0, 0));
+    let ticks = ts.tv_sec() as u64 * DLT_TIMESTAMP_RESOLUTION as u64
+        + ts.tv_nsec() as u64 / (1_000_000_000 / DLT_TIMESTAMP_RESOLUTION as u64);
+    ticks as u32
+}
+
 /// Enable local printing of DLT messages (equivalent to DLT_ENABLE_LOCAL_PRINT)
 pub fn dlt_enable_local_print() {
     DLT_USER.local_print_enabled.store(true, Ordering::Relaxed);
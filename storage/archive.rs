@@ -0,0 +1,206 @@
+// Compressed .dlt files: archiving finished files as <name>.dlt.gz / <name>.dlt.zst
+// and reading them back. The format is detected from the file's magic bytes, so
+// renamed files are read correctly too.
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DltCompression {
+    Gzip,
+    Zstd,
+}
+
+impl DltCompression {
+    // File name suffix appended to ".dlt"
+    pub fn extension(self) -> &'static str {
+        match self {
+            DltCompression::Gzip => "gz",
+            DltCompression::Zstd => "zst",
+        }
+    }
+
+    // Compression of a file, from its first bytes
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&GZIP_MAGIC) {
+            Some(DltCompression::Gzip)
+        } else if header.starts_with(&ZSTD_MAGIC) {
+            Some(DltCompression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    // Compression named by a path's extension, and the path without it:
+    // trace.dlt.zst -> (trace.dlt, Zstd)
+    pub fn from_archive_path<P: AsRef<Path>>(path: P) -> (PathBuf, Option<Self>) {
+        let path = path.as_ref();
+        let compression = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Some(DltCompression::Gzip),
+            Some("zst") => Some(DltCompression::Zstd),
+            _ => None,
+        };
+        match compression {
+            Some(c) => (path.with_extension(""), Some(c)),
+            None => (path.to_path_buf(), None),
+        }
+    }
+
+    // Path of the archive of `path`: trace.dlt -> trace.dlt.zst
+    pub fn archive_path<P: AsRef<Path>>(self, path: P) -> PathBuf {
+        let mut name = path.as_ref().as_os_str().to_owned();
+        name.push(".");
+        name.push(self.extension());
+        PathBuf::from(name)
+    }
+}
+
+impl fmt::Display for DltCompression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DltCompression::Gzip => write!(f, "gzip"),
+            DltCompression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+impl FromStr for DltCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gzip" | "gz" => Ok(DltCompression::Gzip),
+            "zstd" | "zst" => Ok(DltCompression::Zstd),
+            _ => Err(format!("unsupported compression '{}' (gzip or zstd)", s)),
+        }
+    }
+}
+
+// Compress a finished file next to it and remove the original
+// The archive is written under a temporary name and renamed once complete, so a
+// crash leaves the original file in place.
+pub fn compress_file<P: AsRef<Path>>(path: P, compression: DltCompression) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    let archive = compression.archive_path(path);
+    let mut tmp = archive.clone().into_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let mut input = BufReader::new(File::open(path)?);
+    let output = File::create(&tmp)?;
+    let output = match compression {
+        DltCompression::Gzip => {
+            let mut encoder = GzEncoder::new(output, flate2::Compression::default());
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?
+        }
+        DltCompression::Zstd => {
+            let mut encoder = zstd::Encoder::new(output, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?
+        }
+    };
+    output.sync_all()?;
+    fs::rename(&tmp, &archive)?;
+    fs::remove_file(path)?;
+    Ok(archive)
+}
+
+enum Decoder {
+    Plain(File),
+    Gzip(MultiGzDecoder<BufReader<File>>),
+    Zstd(zstd::Decoder<'static, BufReader<File>>),
+}
+
+impl Decoder {
+    fn open(path: &Path) -> io::Result<(Self, Option<DltCompression>)> {
+        let mut file = File::open(path)?;
+        let mut header = [0u8; 4];
+        let mut n = 0;
+        while n < header.len() {
+            match file.read(&mut header[n..])? {
+                0 => break,
+                read => n += read,
+            }
+        }
+        file.rewind()?;
+        let compression = DltCompression::detect(&header[..n]);
+        let decoder = match compression {
+            None => Decoder::Plain(file),
+            Some(DltCompression::Gzip) => Decoder::Gzip(MultiGzDecoder::new(BufReader::new(file))),
+            Some(DltCompression::Zstd) => Decoder::Zstd(zstd::Decoder::new(file)?),
+        };
+        Ok((decoder, compression))
+    }
+}
+
+// A .dlt file, plain or compressed, read as its uncompressed bytes
+// Seeking in a compressed file decompresses from the start (or from the current
+// position when seeking forward).
+pub struct DltFileSource {
+    path: PathBuf,
+    decoder: Decoder,
+    compression: Option<DltCompression>,
+    position: u64, // Uncompressed offset, compressed files only
+}
+
+impl DltFileSource {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (decoder, compression) = Decoder::open(path.as_ref())?;
+        Ok(DltFileSource { path: path.as_ref().to_path_buf(), decoder, compression, position: 0 })
+    }
+
+    pub fn compression(&self) -> Option<DltCompression> {
+        self.compression
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Read for DltFileSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = match &mut self.decoder {
+            Decoder::Plain(file) => file.read(buf)?,
+            Decoder::Gzip(decoder) => decoder.read(buf)?,
+            Decoder::Zstd(decoder) => decoder.read(buf)?,
+        };
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for DltFileSource {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        if let Decoder::Plain(file) = &mut self.decoder {
+            return file.seek(pos);
+        }
+        let target = match pos {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "Seek before the start of the file")
+            })?,
+            SeekFrom::End(_) => {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "Cannot seek from the end of a compressed file"));
+            }
+        };
+        if target < self.position {
+            self.decoder = Decoder::open(&self.path)?.0;
+            self.position = 0;
+        }
+        let skip = target - self.position;
+        let skipped = io::copy(&mut self.by_ref().take(skip), &mut io::sink())?;
+        if skipped < skip {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Seek past the end of a compressed file"));
+        }
+        Ok(self.position)
+    }
+}
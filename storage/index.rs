@@ -0,0 +1,368 @@
+// Message index for large .dlt files, built over a memory-mapped file
+//   message number -> file offset        (constant time)
+//   storage time   -> message numbers    (binary search over a time-sorted list)
+//   app/ctx        -> message numbers    (postings, ascending)
+// The index can be kept in a sidecar file next to the trace ("<file>.idx"), which
+// is reused while the trace is unchanged and extended when records were appended.
+// The sidecar records the trace's modification time and a checksum of its first
+// bytes, so a rewritten trace of the same (or greater) length is indexed anew.
+use crate::archive::DltCompression;
+use dlt_core::{
+    AppId, ContextId, DltConvertError, DltFrame, DltFrameDecoder, DltMessage, DltMessageRef, DltMessageV2, DltParseError,
+};
+use memmap2::Mmap;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const DLT_INDEX_MAGIC: [u8; 8] = *b"DLTIDX02";
+const INDEX_CHUNK_SIZE: usize = 1024 * 1024;
+// Bytes at the start of the trace covered by the sidecar checksum
+const INDEX_PREFIX_SIZE: usize = 4096;
+
+// Storage header time in microseconds since the Unix epoch
+pub fn storage_time_us(seconds: u32, microseconds: u32) -> u64 {
+    seconds as u64 * 1_000_000 + microseconds as u64
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DltFileIndex {
+    offsets: Vec<u64>,
+    times: Vec<u64>,        // Storage time (us) per message
+    time_order: Vec<u32>,   // Message numbers sorted by time, stable
+    postings: BTreeMap<(AppId, ContextId), Vec<u32>>,
+    indexed_len: u64,       // File bytes covered; a cut-off last record is not
+    skipped_bytes: u64,
+    prefix_checksum: u64,   // Of the first indexed bytes (up to INDEX_PREFIX_SIZE)
+    file_mtime: u64,        // Trace modification time (ns) when the sidecar was saved
+}
+
+impl DltFileIndex {
+    // Index all complete records of a file image; fails on a version 2 record
+    // whose application or context ID does not fit 4 bytes
+    pub fn build(data: &[u8]) -> io::Result<Self> {
+        let mut index = DltFileIndex::default();
+        index.extend(data)?;
+        Ok(index)
+    }
+
+    // Index records appended after indexed_len
+    fn extend(&mut self, data: &[u8]) -> io::Result<()> {
+        let start = self.indexed_len;
+        let mut decoder = DltFrameDecoder::new();
+        let first = self.offsets.len();
+        for chunk in data[start as usize..].chunks(INDEX_CHUNK_SIZE) {
+            decoder.push(chunk);
+            while let Some(frame) = decoder.next_frame() {
+                let (time, apid, ctid) = match frame {
+                    DltFrame::Message(msg) => {
+                        let header = msg.storage_header();
+                        let time = storage_time_us(header.seconds, header.microseconds);
+                        (time, msg.apid().unwrap_or_default(), msg.ctid().unwrap_or_default())
+                    }
+                    DltFrame::MessageV2(msg) => {
+                        let time = storage_time_us(msg.storage_header.seconds, msg.storage_header.microseconds);
+                        let invalid = |e| {
+                            let offset = start + decoder.frame_offset();
+                            io::Error::new(io::ErrorKind::InvalidData, format!("Message at offset {}: {}", offset, e))
+                        };
+                        let apid = msg.apid.as_deref().map(AppId::try_new).transpose().map_err(invalid)?;
+                        let ctid = msg.ctid.as_deref().map(ContextId::try_new).transpose().map_err(invalid)?;
+                        (time, apid.unwrap_or_default(), ctid.unwrap_or_default())
+                    }
+                    DltFrame::Skipped(_) => continue,
+                };
+                // Message numbers and the sidecar count are u32
+                let number = count_u32(self.offsets.len() + 1)? - 1;
+                self.offsets.push(start + decoder.frame_offset());
+                self.times.push(time);
+                self.postings.entry((apid, ctid)).or_default().push(number);
+            }
+        }
+        // Bytes left in the decoder are a record still being written
+        self.indexed_len = start + decoder.stream_offset();
+        self.skipped_bytes += decoder.skipped_bytes();
+        self.prefix_checksum = prefix_checksum(data, self.indexed_len);
+
+        // Files are mostly in time order already, so this is close to linear
+        self.time_order.extend(first as u32..self.offsets.len() as u32);
+        let times = &self.times;
+        self.time_order.sort_by_key(|&n| times[n as usize]);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    // File offset of message number `n` (its storage header)
+    pub fn offset(&self, n: usize) -> Option<u64> {
+        self.offsets.get(n).copied()
+    }
+
+    // Storage time of message number `n` in microseconds
+    pub fn time(&self, n: usize) -> Option<u64> {
+        self.times.get(n).copied()
+    }
+
+    // Earliest and latest storage time
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        let first = *self.time_order.first()?;
+        let last = *self.time_order.last()?;
+        Some((self.times[first as usize], self.times[last as usize]))
+    }
+
+    // Earliest message at or after `time_us`
+    pub fn first_at_or_after(&self, time_us: u64) -> Option<usize> {
+        let i = self.time_order.partition_point(|&n| self.times[n as usize] < time_us);
+        self.time_order.get(i).map(|&n| n as usize)
+    }
+
+    // Message numbers with start <= time < end, in time order
+    pub fn in_time_range(&self, start_us: u64, end_us: u64) -> &[u32] {
+        let from = self.time_order.partition_point(|&n| self.times[n as usize] < start_us);
+        let to = self.time_order.partition_point(|&n| self.times[n as usize] < end_us);
+        &self.time_order[from..to.max(from)]
+    }
+
+    // Message numbers of one application and context, ascending
+    pub fn messages_of(&self, apid: AppId, ctid: ContextId) -> &[u32] {
+        self.postings.get(&(apid, ctid)).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // Application/context pairs and their message counts
+    pub fn contexts(&self) -> impl Iterator<Item = (AppId, ContextId, usize)> + '_ {
+        self.postings.iter().map(|(&(apid, ctid), numbers)| (apid, ctid, numbers.len()))
+    }
+
+    // Bytes of the file that were not part of a valid record
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped_bytes
+    }
+
+    // File bytes covered by the index
+    pub fn indexed_len(&self) -> u64 {
+        self.indexed_len
+    }
+
+    // Sidecar layout (little endian):
+    //   "DLTIDX02", indexed_len u64, skipped_bytes u64, prefix_checksum u64,
+    //   file_mtime u64, count u32,
+    //   offsets [u64; count], times [u64; count], time_order [u32; count],
+    //   posting count u32, then per posting: apid [4], ctid [4], n u32, numbers [u32; n]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        w.write_all(&DLT_INDEX_MAGIC)?;
+        w.write_all(&self.indexed_len.to_le_bytes())?;
+        w.write_all(&self.skipped_bytes.to_le_bytes())?;
+        w.write_all(&self.prefix_checksum.to_le_bytes())?;
+        w.write_all(&self.file_mtime.to_le_bytes())?;
+        w.write_all(&count_u32(self.offsets.len())?.to_le_bytes())?;
+        for offset in &self.offsets {
+            w.write_all(&offset.to_le_bytes())?;
+        }
+        for time in &self.times {
+            w.write_all(&time.to_le_bytes())?;
+        }
+        for n in &self.time_order {
+            w.write_all(&n.to_le_bytes())?;
+        }
+        w.write_all(&count_u32(self.postings.len())?.to_le_bytes())?;
+        for ((apid, ctid), numbers) in &self.postings {
+            w.write_all(&apid.0)?;
+            w.write_all(&ctid.0)?;
+            w.write_all(&count_u32(numbers.len())?.to_le_bytes())?;
+            for n in numbers {
+                w.write_all(&n.to_le_bytes())?;
+            }
+        }
+        w.into_inner().map_err(|e| e.into_error())?.sync_data()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut r = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if magic != DLT_INDEX_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a DLT index file"));
+        }
+        let indexed_len = read_u64(&mut r)?;
+        let skipped_bytes = read_u64(&mut r)?;
+        let prefix_checksum = read_u64(&mut r)?;
+        let file_mtime = read_u64(&mut r)?;
+        let count = read_u32(&mut r)? as usize;
+        let offsets = (0..count).map(|_| read_u64(&mut r)).collect::<io::Result<Vec<_>>>()?;
+        let times = (0..count).map(|_| read_u64(&mut r)).collect::<io::Result<Vec<_>>>()?;
+        let time_order = (0..count).map(|_| read_u32(&mut r)).collect::<io::Result<Vec<_>>>()?;
+        let corrupt = || io::Error::new(io::ErrorKind::InvalidData, "Corrupt DLT index file");
+        // Offsets ascend and point into the indexed bytes, so they can be used unchecked
+        if offsets.windows(2).any(|w| w[0] >= w[1]) || offsets.last().is_some_and(|&o| o >= indexed_len) {
+            return Err(corrupt());
+        }
+        if time_order.iter().any(|&n| n as usize >= count) {
+            return Err(corrupt());
+        }
+        let mut postings = BTreeMap::new();
+        for _ in 0..read_u32(&mut r)? {
+            let mut ids = [0u8; 8];
+            r.read_exact(&mut ids)?;
+            let n = read_u32(&mut r)?;
+            let numbers = (0..n).map(|_| read_u32(&mut r)).collect::<io::Result<Vec<_>>>()?;
+            if numbers.iter().any(|&n| n as usize >= count) {
+                return Err(corrupt());
+            }
+            let apid = AppId([ids[0], ids[1], ids[2], ids[3]]);
+            let ctid = ContextId([ids[4], ids[5], ids[6], ids[7]]);
+            postings.insert((apid, ctid), numbers);
+        }
+        Ok(DltFileIndex {
+            offsets,
+            times,
+            time_order,
+            postings,
+            indexed_len,
+            skipped_bytes,
+            prefix_checksum,
+            file_mtime,
+        })
+    }
+}
+
+// FNV-1a over the first indexed bytes
+fn prefix_checksum(data: &[u8], indexed_len: u64) -> u64 {
+    let len = (indexed_len as usize).min(INDEX_PREFIX_SIZE).min(data.len());
+    data[..len].iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn count_u32(count: usize) -> io::Result<u32> {
+    u32::try_from(count).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Too many messages to index"))
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+// Sidecar index path: "<file>.idx"
+pub fn sidecar_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut name = path.as_ref().as_os_str().to_owned();
+    name.push(".idx");
+    PathBuf::from(name)
+}
+
+// Memory-mapped .dlt file with its index
+pub struct DltMappedFile {
+    mmap: Mmap,
+    index: DltFileIndex,
+}
+
+impl DltMappedFile {
+    // Map and index a file in memory
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mmap = map_file(path.as_ref())?;
+        let index = DltFileIndex::build(&mmap)?;
+        Ok(DltMappedFile { mmap, index })
+    }
+
+    // Map a file and use its sidecar index: reused if the file is unchanged (same
+    // length and modification time), extended if it grew, rebuilt (and rewritten)
+    // otherwise. Either way the first bytes must still match the sidecar checksum.
+    pub fn open_with_sidecar<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        // Taken before mapping: a later append only makes the sidecar look stale
+        let mtime = file_mtime(path.as_ref())?;
+        let mmap = map_file(path.as_ref())?;
+        let sidecar = sidecar_path(path.as_ref());
+        let len = mmap.len() as u64;
+        let (mut index, changed) = match DltFileIndex::load(&sidecar) {
+            Ok(index) if index.indexed_len > len || !same_prefix(&mmap, &index) => (DltFileIndex::build(&mmap)?, true),
+            Ok(index) if index.indexed_len == len && index.file_mtime == mtime => (index, false),
+            Ok(mut index) if index.indexed_len < len && starts_record(&mmap, &index) => {
+                index.extend(&mmap)?;
+                (index, true)
+            }
+            _ => (DltFileIndex::build(&mmap)?, true),
+        };
+        if changed {
+            index.file_mtime = mtime;
+            index.save(&sidecar)?;
+        }
+        Ok(DltMappedFile { mmap, index })
+    }
+
+    pub fn index(&self) -> &DltFileIndex {
+        &self.index
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    // Whole mapped file
+    pub fn data(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    // Message number `n` without copying (version 1 records)
+    pub fn message_ref(&self, n: usize) -> Option<Result<DltMessageRef<'_>, DltParseError>> {
+        let offset = self.index.offset(n)? as usize;
+        Some(DltMessageRef::from_bytes(&self.mmap[offset..]))
+    }
+
+    // Message number `n`, version 2 records converted to version 1
+    pub fn message(&self, n: usize) -> Option<Result<DltMessage, DltConvertError>> {
+        let offset = self.index.offset(n)? as usize;
+        let bytes = &self.mmap[offset..];
+        Some(match DltMessageRef::from_bytes(bytes) {
+            Err(DltParseError::UnsupportedVersion { .. }) => DltMessageV2::from_bytes(bytes)
+                .map_err(DltConvertError::from)
+                .and_then(|msg| msg.to_v1().map_err(DltConvertError::from)),
+            result => result.map(|msg| msg.to_message()).map_err(DltConvertError::from),
+        })
+    }
+}
+
+fn map_file(path: &Path) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+    // The trace must not be truncated while mapped; appending is fine
+    let mmap = unsafe { Mmap::map(&file) }?;
+    if DltCompression::detect(&mmap).is_some() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Compressed files cannot be mapped, use DltFileReader"));
+    }
+    Ok(mmap)
+}
+
+// Modification time in nanoseconds since the Unix epoch
+fn file_mtime(path: &Path) -> io::Result<u64> {
+    let modified = std::fs::metadata(path)?.modified()?;
+    Ok(modified.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64))
+}
+
+fn same_prefix(data: &[u8], index: &DltFileIndex) -> bool {
+    prefix_checksum(data, index.indexed_len) == index.prefix_checksum
+}
+
+// A sidecar only fits a grown file if its last record is still where it was
+fn starts_record(data: &[u8], index: &DltFileIndex) -> bool {
+    match index.offsets.last() {
+        Some(&offset) => DltMessageRef::from_bytes(&data[offset as usize..]).is_ok()
+            || DltMessageV2::from_bytes(&data[offset as usize..]).is_ok(),
+        None => true,
+    }
+}
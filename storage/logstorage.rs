@@ -0,0 +1,571 @@
+// Offline log storage: messages are written to rotating .dlt files selected by
+// filter sections of a dlt_logstorage.conf (COVESA format):
+//
+//   [FILTER1]
+//   LogAppName=APP1,APP2     ; .* matches all applications
+//   ContextName=.*
+//   LogLevel=DLT_LOG_WARN    ; log messages up to this level
+//   File=errors              ; files errors_001_20240101-120000.dlt, ...
+//   FileSize=500000          ; bytes per file
+//   NOFiles=5                ; files kept, the oldest are deleted
+//   SyncBehavior=ON_MSG      ; optional: ON_MSG (default), ON_FILE_SIZE, ON_DAEMON_EXIT
+//   EcuID=ECU1               ; optional
+//   Compression=zstd         ; optional: gzip or zstd for full files (GzipCompression=on: gzip)
+//
+// A message is written once to every filter it matches.
+use crate::archive::{compress_file, DltCompression};
+use crate::writer::{DltFileWriter, DltSyncMode};
+use dlt_core::{AppId, ContextId, DltMessageRef, DltMessageV2, EcuId, LogLevel, MessageInfo, MessageType};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+
+pub const DLT_LOGSTORAGE_CONFIG_FILE: &str = "dlt_logstorage.conf";
+
+// When records of a filter are forced to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DltLogStorageSync {
+    #[default]
+    OnMsg,        // After every message
+    OnFileSize,   // When a file is full and closed
+    OnDaemonExit, // Only on shutdown (DltLogStorage::sync)
+}
+
+impl DltLogStorageSync {
+    fn sync_mode(self) -> DltSyncMode {
+        match self {
+            DltLogStorageSync::OnMsg => DltSyncMode::EveryMessage,
+            DltLogStorageSync::OnFileSize => DltSyncMode::OnFlush,
+            DltLogStorageSync::OnDaemonExit => DltSyncMode::Never,
+        }
+    }
+}
+
+// Configuration error with its line (the section header for a missing key)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DltLogStorageConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DltLogStorageConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for DltLogStorageConfigError {}
+
+// One [FILTER] section; None in apids/ctids/ecu matches everything
+#[derive(Debug, Clone, PartialEq)]
+pub struct DltLogStorageFilter {
+    pub name: String,
+    pub apids: Option<Vec<AppId>>,
+    pub ctids: Option<Vec<ContextId>>,
+    pub ecu: Option<EcuId>,
+    pub log_level: LogLevel,
+    pub file: String,
+    pub file_size: u64,
+    pub max_files: usize,
+    pub sync: DltLogStorageSync,
+    pub compression: Option<DltCompression>,
+}
+
+impl DltLogStorageFilter {
+    // Control messages are never stored; log messages are checked against the level
+    pub fn matches(&self, ecu: EcuId, apid: Option<AppId>, ctid: Option<ContextId>, info: Option<MessageInfo>) -> bool {
+        let message_type = info.and_then(|info| info.message_type());
+        if message_type == Some(MessageType::Control) {
+            return false;
+        }
+        if message_type == Some(MessageType::Log) {
+            match info.and_then(|info| info.log_level()) {
+                Some(level) if level as u8 <= self.log_level as u8 => {}
+                _ => return false,
+            }
+        }
+        id_matches(&self.apids, apid)
+            && id_matches(&self.ctids, ctid)
+            && self.ecu.is_none_or(|filter_ecu| filter_ecu == ecu)
+    }
+}
+
+// No list matches any ID; a list never matches a message without IDs
+fn id_matches<T: PartialEq>(ids: &Option<Vec<T>>, id: Option<T>) -> bool {
+    match ids {
+        None => true,
+        Some(ids) => id.is_some_and(|id| ids.contains(&id)),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DltLogStorageConfig {
+    pub filters: Vec<DltLogStorageFilter>,
+}
+
+impl DltLogStorageConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = fs::read_to_string(path.as_ref())?;
+        Self::parse(&text).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.as_ref().display(), e))
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, DltLogStorageConfigError> {
+        let mut filters = Vec::new();
+        let mut section: Option<(usize, SectionBuilder)> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let error = |message: String| DltLogStorageConfigError { line: line_no, message };
+            let line = line.split(['#', ';']).next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if let Some((start, builder)) = section.take() {
+                    push_filter(&mut filters, builder.build(start)?, start)?;
+                }
+                section = Some((line_no, SectionBuilder::new(name.trim())));
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected key=value, got '{}'", line)))?;
+            let (_, builder) = section
+                .as_mut()
+                .ok_or_else(|| error("key outside of a [FILTER] section".to_string()))?;
+            builder.set(key.trim(), value.trim()).map_err(error)?;
+        }
+        if let Some((start, builder)) = section {
+            push_filter(&mut filters, builder.build(start)?, start)?;
+        }
+        Ok(DltLogStorageConfig { filters })
+    }
+}
+
+// Two filters writing the same files would rotate and delete each other's files
+fn push_filter(
+    filters: &mut Vec<DltLogStorageFilter>,
+    filter: DltLogStorageFilter,
+    line: usize,
+) -> Result<(), DltLogStorageConfigError> {
+    if let Some(other) = filters.iter().find(|other| other.file == filter.file) {
+        let message = format!("[{}] uses File '{}' of [{}] again", filter.name, filter.file, other.name);
+        return Err(DltLogStorageConfigError { line, message });
+    }
+    filters.push(filter);
+    Ok(())
+}
+
+#[derive(Default)]
+struct SectionBuilder {
+    name: String,
+    apids: Option<Option<Vec<AppId>>>,
+    ctids: Option<Option<Vec<ContextId>>>,
+    ecu: Option<EcuId>,
+    log_level: Option<LogLevel>,
+    file: Option<String>,
+    file_size: Option<u64>,
+    max_files: Option<usize>,
+    sync: DltLogStorageSync,
+    compression: Option<DltCompression>,
+}
+
+impl SectionBuilder {
+    fn new(name: &str) -> Self {
+        SectionBuilder { name: name.to_string(), ..Default::default() }
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "LogAppName" => self.apids = Some(parse_id_list(value)?),
+            "ContextName" => self.ctids = Some(parse_id_list(value)?),
+            "EcuID" => self.ecu = Some(value.parse().map_err(|e| format!("EcuID '{}': {}", value, e))?),
+            "LogLevel" => self.log_level = Some(parse_log_level(value)?),
+            "File" => {
+                if value.is_empty() || value.contains(['/', '\\']) || value.starts_with('.') {
+                    return Err(format!("File '{}' is not a plain file name", value));
+                }
+                self.file = Some(value.to_string());
+            }
+            "FileSize" => match value.parse() {
+                Ok(size) if size > 0 => self.file_size = Some(size),
+                _ => return Err(format!("FileSize '{}' is not a positive number", value)),
+            },
+            "NOFiles" => match value.parse() {
+                Ok(count) if count > 0 => self.max_files = Some(count),
+                _ => return Err(format!("NOFiles '{}' is not a positive number", value)),
+            },
+            "SyncBehavior" => {
+                self.sync = match value {
+                    "ON_MSG" => DltLogStorageSync::OnMsg,
+                    "ON_FILE_SIZE" => DltLogStorageSync::OnFileSize,
+                    "ON_DAEMON_EXIT" => DltLogStorageSync::OnDaemonExit,
+                    _ => return Err(format!("unsupported SyncBehavior '{}'", value)),
+                }
+            }
+            "Compression" => {
+                self.compression = match value {
+                    "none" | "off" => None,
+                    _ => Some(value.parse()?),
+                }
+            }
+            "GzipCompression" => {
+                self.compression = match value {
+                    "on" => Some(DltCompression::Gzip),
+                    "off" => None,
+                    _ => return Err(format!("GzipCompression '{}' is not on or off", value)),
+                }
+            }
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+        Ok(())
+    }
+
+    fn build(self, line: usize) -> Result<DltLogStorageFilter, DltLogStorageConfigError> {
+        let missing = |key: &str| DltLogStorageConfigError { line, message: format!("[{}] has no {}", self.name, key) };
+        if self.apids.is_none() && self.ctids.is_none() {
+            return Err(missing("LogAppName or ContextName"));
+        }
+        Ok(DltLogStorageFilter {
+            log_level: self.log_level.ok_or_else(|| missing("LogLevel"))?,
+            file: self.file.clone().ok_or_else(|| missing("File"))?,
+            file_size: self.file_size.ok_or_else(|| missing("FileSize"))?,
+            max_files: self.max_files.ok_or_else(|| missing("NOFiles"))?,
+            apids: self.apids.flatten(),
+            ctids: self.ctids.flatten(),
+            ecu: self.ecu,
+            sync: self.sync,
+            compression: self.compression,
+            name: self.name,
+        })
+    }
+}
+
+// Comma separated IDs; ".*" (or "*") matches all
+fn parse_id_list<T: std::str::FromStr<Err = dlt_core::DltIdError>>(value: &str) -> Result<Option<Vec<T>>, String> {
+    if value == ".*" || value == "*" {
+        return Ok(None);
+    }
+    value
+        .split(',')
+        .map(|id| id.trim().parse().map_err(|e| format!("ID '{}': {}", id.trim(), e)))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+// DLT_LOG_FATAL .. DLT_LOG_VERBOSE or the numeric value 1 .. 6
+fn parse_log_level(value: &str) -> Result<LogLevel, String> {
+    let level = match value {
+        "DLT_LOG_FATAL" => Some(LogLevel::Fatal),
+        "DLT_LOG_ERROR" => Some(LogLevel::Error),
+        "DLT_LOG_WARN" => Some(LogLevel::Warn),
+        "DLT_LOG_INFO" => Some(LogLevel::Info),
+        "DLT_LOG_DEBUG" => Some(LogLevel::Debug),
+        "DLT_LOG_VERBOSE" => Some(LogLevel::Verbose),
+        _ => value.parse().ok().and_then(LogLevel::from_u8),
+    };
+    level.ok_or_else(|| format!("unsupported LogLevel '{}'", value))
+}
+
+// Numbered files <name>_<NNN>_<date>.dlt in one directory, rotated by size
+// Numbering continues after files left by an earlier run, which count
+// towards the file limit. With compression, finished files are archived in the
+// background as <name>_<NNN>_<date>.dlt.gz/.zst.
+pub struct DltRotatingFile {
+    dir: PathBuf,
+    name: String,
+    max_size: u64,
+    max_files: usize,
+    sync: DltSyncMode,
+    compression: Option<DltCompression>,
+    writer: Option<DltFileWriter>,
+    files: VecDeque<RotatedFile>, // Oldest first, including the open one
+    next_index: u32,
+}
+
+struct RotatedFile {
+    index: u32,
+    path: PathBuf,
+    archiving: Option<JoinHandle<io::Result<PathBuf>>>,
+}
+
+impl RotatedFile {
+    // Wait for a running compression; the file's path is then the archive's
+    fn finish_archiving(&mut self) -> io::Result<()> {
+        let Some(handle) = self.archiving.take() else {
+            return Ok(());
+        };
+        let result = handle
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("compression thread panicked")));
+        match result {
+            Ok(archive) => {
+                self.path = archive;
+                Ok(())
+            }
+            Err(e) => Err(io::Error::new(e.kind(), format!("Failed to compress {}: {}", self.path.display(), e))),
+        }
+    }
+}
+
+impl DltRotatingFile {
+    pub fn open<P: AsRef<Path>>(dir: P, name: &str, max_size: u64, max_files: usize, sync: DltSyncMode) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let mut found = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|f| f.to_str()) else {
+                continue;
+            };
+            // Archive of a compression cut short by an exit; its source is still
+            // there and is compressed again (see with_compression)
+            if file_name.strip_suffix(".tmp").is_some_and(|archive| file_index(archive, name).is_some()) {
+                remove_file(&path)?;
+                continue;
+            }
+            if let Some(index) = file_index(file_name, name) {
+                found.push(RotatedFile { index, path, archiving: None });
+            }
+        }
+        // Archives sort before uncompressed files of the same index
+        found.sort_by_key(|file| (file.index, is_uncompressed(&file.path)));
+        let mut files: Vec<RotatedFile> = Vec::new();
+        for file in found {
+            // Exit between renaming the finished archive and removing its source
+            if is_uncompressed(&file.path) && files.last().is_some_and(|last| last.index == file.index) {
+                remove_file(&file.path)?;
+                continue;
+            }
+            files.push(file);
+        }
+        let next_index = files.last().map_or(1, |file| file.index + 1);
+        Ok(DltRotatingFile {
+            dir,
+            name: name.to_string(),
+            max_size,
+            max_files: max_files.max(1),
+            sync,
+            compression: None,
+            writer: None,
+            files: files.into(),
+            next_index,
+        })
+    }
+
+    // Compress files once they are full; files left uncompressed by an earlier
+    // run are compressed right away
+    pub fn with_compression(mut self, compression: Option<DltCompression>) -> Self {
+        self.compression = compression;
+        if let Some(compression) = compression {
+            for file in &mut self.files {
+                if is_uncompressed(&file.path) {
+                    file.archiving = Some(spawn_compression(file.path.clone(), compression));
+                }
+            }
+        }
+        self
+    }
+
+    // Write a record, starting a new file first if it would exceed the size limit
+    // A record larger than the limit gets a file of its own.
+    pub fn write_record(&mut self, record: &[u8]) -> io::Result<()> {
+        let full = self
+            .writer
+            .as_ref()
+            .is_some_and(|w| w.size() > 0 && w.size() + record.len() as u64 > self.max_size);
+        if full {
+            self.close()?;
+        }
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => self.create_file()?,
+        };
+        writer.write_record(record)
+    }
+
+    fn create_file(&mut self) -> io::Result<&mut DltFileWriter> {
+        // Report failed compressions of earlier files
+        let mut result = Ok(());
+        for file in self.files.iter_mut().filter(|f| f.archiving.as_ref().is_some_and(|h| h.is_finished())) {
+            result = result.and(file.finish_archiving());
+        }
+        // The new file counts, so make room for it first
+        while self.files.len() >= self.max_files {
+            if let Some(mut oldest) = self.files.pop_front() {
+                let archived = oldest.finish_archiving();
+                remove_file(&oldest.path)?;
+                archived?;
+            }
+        }
+        result?;
+        let date = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let path = self.dir.join(format!("{}_{:03}_{}.dlt", self.name, self.next_index, date));
+        let writer = DltFileWriter::create(&path)?.with_sync(self.sync);
+        self.files.push_back(RotatedFile { index: self.next_index, path, archiving: None });
+        self.next_index += 1;
+        Ok(self.writer.insert(writer))
+    }
+
+    // Finish the current file; the next record starts a new one
+    pub fn close(&mut self) -> io::Result<()> {
+        let Some(mut writer) = self.writer.take() else {
+            return Ok(());
+        };
+        writer.flush()?;
+        drop(writer);
+        if let (Some(compression), Some(file)) = (self.compression, self.files.back_mut()) {
+            file.archiving = Some(spawn_compression(file.path.clone(), compression));
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    // Flush and fsync regardless of the sync mode, and wait for running compressions
+    // (an exit would leave them unfinished)
+    pub fn sync(&mut self) -> io::Result<()> {
+        let synced = match &mut self.writer {
+            Some(writer) => writer.sync(),
+            None => Ok(()),
+        };
+        synced.and(self.finish_archiving())
+    }
+
+    // Wait until finished files are compressed
+    pub fn finish_archiving(&mut self) -> io::Result<()> {
+        self.files.iter_mut().map(RotatedFile::finish_archiving).fold(Ok(()), io::Result::and)
+    }
+
+    // Files on disk, oldest first (archives still being written are listed uncompressed)
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|file| file.path.as_path())
+    }
+}
+
+impl Drop for DltRotatingFile {
+    fn drop(&mut self) {
+        let _ = self.finish_archiving();
+    }
+}
+
+fn spawn_compression(path: PathBuf, compression: DltCompression) -> JoinHandle<io::Result<PathBuf>> {
+    thread::spawn(move || compress_file(path, compression))
+}
+
+// Index of a file named exactly <name>_<NNN>_<YYYYmmdd-HHMMSS>.dlt, .dlt.gz or .dlt.zst
+// (as written by create_file), so the files of a filter named e.g. "log_2" are
+// never taken for files of "log"
+fn file_index(file_name: &str, name: &str) -> Option<u32> {
+    let rest = file_name.strip_prefix(name)?.strip_prefix('_')?;
+    let (index, rest) = rest.split_once('_')?;
+    let date = [".dlt", ".dlt.gz", ".dlt.zst"].iter().find_map(|ext| rest.strip_suffix(ext))?;
+    let (day, time) = date.split_once('-')?;
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if index.len() < 3 || !digits(index) || day.len() != 8 || !digits(day) || time.len() != 6 || !digits(time) {
+        return None;
+    }
+    index.parse().ok()
+}
+
+fn is_uncompressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "dlt")
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// Filters with their files
+pub struct DltLogStorage {
+    dir: PathBuf,
+    filters: Vec<(DltLogStorageFilter, DltRotatingFile)>,
+}
+
+impl DltLogStorage {
+    // Storage configured by <dir>/dlt_logstorage.conf, writing into <dir>
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let config = DltLogStorageConfig::load(dir.as_ref().join(DLT_LOGSTORAGE_CONFIG_FILE))?;
+        Self::with_config(dir, config)
+    }
+
+    pub fn with_config<P: AsRef<Path>>(dir: P, config: DltLogStorageConfig) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let filters = config
+            .filters
+            .into_iter()
+            .map(|filter| {
+                let file = DltRotatingFile::open(&dir, &filter.file, filter.file_size, filter.max_files, filter.sync.sync_mode())?
+                    .with_compression(filter.compression);
+                Ok((filter, file))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(DltLogStorage { dir, filters })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn filters(&self) -> impl Iterator<Item = &DltLogStorageFilter> {
+        self.filters.iter().map(|(filter, _)| filter)
+    }
+
+    // Store a message (with storage header) in every matching filter's file
+    // Returns the number of filters it was written to, or the first failed write
+    // after trying all of them.
+    pub fn store(&mut self, msg: &DltMessageRef) -> io::Result<usize> {
+        let ecu = msg.header_ecu().unwrap_or_else(|| msg.ecu());
+        self.store_record(msg.as_bytes(), ecu, msg.apid(), msg.ctid(), msg.message_info())
+    }
+
+    pub fn store_v2(&mut self, msg: &DltMessageV2) -> io::Result<usize> {
+        // Filters match 4-byte IDs; longer version 2 IDs cannot be matched
+        let invalid = |e: &dyn fmt::Display| io::Error::new(io::ErrorKind::InvalidInput, e.to_string());
+        let ecu = msg.ecu.as_deref().map(EcuId::try_new).transpose().map_err(|e| invalid(&e))?;
+        let apid = msg.apid.as_deref().map(AppId::try_new).transpose().map_err(|e| invalid(&e))?;
+        let ctid = msg.ctid.as_deref().map(ContextId::try_new).transpose().map_err(|e| invalid(&e))?;
+        let ecu = ecu.unwrap_or(msg.storage_header.ecu);
+        let record = msg.to_bytes().map_err(|e| invalid(&e))?;
+        self.store_record(&record, ecu, apid, ctid, Some(msg.msin))
+    }
+
+    fn store_record(&mut self, record: &[u8], ecu: EcuId, apid: Option<AppId>, ctid: Option<ContextId>,
+                    info: Option<MessageInfo>) -> io::Result<usize> {
+        // A failing file does not keep the record from the other filters; the
+        // first error is returned once all are written
+        let mut stored = 0;
+        let mut result = Ok(());
+        for (filter, file) in &mut self.filters {
+            if filter.matches(ecu, apid, ctid, info) {
+                match file.write_record(record) {
+                    Ok(()) => stored += 1,
+                    Err(e) => result = result.and(Err(e)),
+                }
+            }
+        }
+        result.map(|()| stored)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.filters.iter_mut().try_for_each(|(_, file)| file.flush())
+    }
+
+    // Force all files to disk and finish compressions (shutdown, or ON_DAEMON_EXIT
+    // filters on demand); every filter is synced even if one fails
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.filters.iter_mut().map(|(_, file)| file.sync()).fold(Ok(()), io::Result::and)
+    }
+}
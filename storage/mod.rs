@@ -0,0 +1,15 @@
+// Storage module: .dlt files (storage header + message records, as written by
+// COVESA dlt-receive -o and dlt-viewer)
+pub mod reader;
+pub mod writer;
+pub mod index;
+pub mod logstorage;
+pub mod archive;
+pub mod pcapng;
+
+pub use reader::*;
+pub use writer::*;
+pub use index::*;
+pub use logstorage::*;
+pub use archive::*;
+pub use pcapng::*;
@@ -0,0 +1,214 @@
+// PCAPNG export for Wireshark: each message becomes one packet, either
+//   Udp: a synthetic IPv4/UDP datagram from port 3490 to the COVESA multicast
+//        group 225.0.0.37:3490 (link type RAW), or
+//   Dlt: the bare message (link type AUTOSAR_DLT)
+// Packet times come from the storage header. Every ECU gets its own interface
+// (named after the ECU ID; for UDP also its own source address, 10.0.0.1 onwards).
+use dlt_core::{DltMessage, DltMessageRef, EcuId};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+const STORAGE_HEADER_SIZE: usize = 16;
+
+const BLOCK_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const BLOCK_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const BLOCK_ENHANCED_PACKET: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+
+const OPT_END: u16 = 0;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_NAME: u16 = 2;
+const OPT_IF_TSRESOL: u16 = 9;
+
+const LINKTYPE_RAW: u16 = 101;
+const LINKTYPE_AUTOSAR_DLT: u16 = 294;
+
+const DLT_UDP_PORT: u16 = 3490;
+const DLT_MULTICAST_ADDRESS: [u8; 4] = [225, 0, 0, 37];
+const IPV4_HEADER_SIZE: usize = 20;
+const UDP_HEADER_SIZE: usize = 8;
+const SNAPLEN: u32 = 0x0004_0000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DltPcapLinkType {
+    #[default]
+    Udp,
+    Dlt,
+}
+
+impl FromStr for DltPcapLinkType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "udp" => Ok(DltPcapLinkType::Udp),
+            "dlt" => Ok(DltPcapLinkType::Dlt),
+            _ => Err(format!("unknown link type '{}' (udp or dlt)", s)),
+        }
+    }
+}
+
+pub struct DltPcapWriter<W: Write = BufWriter<File>> {
+    out: W,
+    link_type: DltPcapLinkType,
+    interfaces: HashMap<EcuId, u32>,
+    ip_id: u16,
+    packets_written: u64,
+}
+
+impl DltPcapWriter<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P, link_type: DltPcapLinkType) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), link_type)
+    }
+}
+
+impl<W: Write> DltPcapWriter<W> {
+    // Starts the output with a section header
+    pub fn new(mut out: W, link_type: DltPcapLinkType) -> io::Result<Self> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes()); // Version 1.0
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.extend_from_slice(&(-1i64).to_le_bytes()); // Section length not known
+        push_option(&mut body, OPT_SHB_USERAPPL, b"libdlt-ng");
+        push_option(&mut body, OPT_END, &[]);
+        write_block(&mut out, BLOCK_SECTION_HEADER, &body)?;
+        Ok(DltPcapWriter { out, link_type, interfaces: HashMap::new(), ip_id: 0, packets_written: 0 })
+    }
+
+    // ECU for the interface: the standard header ECU ID, else the storage header's
+    pub fn write_message(&mut self, msg: &DltMessage) -> io::Result<()> {
+        let ecu = msg.standard_header.ecu.unwrap_or(msg.storage_header.ecu);
+        let bytes = msg.to_bytes();
+        self.write_packet(ecu, msg.storage_header.seconds, msg.storage_header.microseconds, &bytes[STORAGE_HEADER_SIZE..])
+    }
+
+    pub fn write_message_ref(&mut self, msg: &DltMessageRef) -> io::Result<()> {
+        let ecu = msg.header_ecu().unwrap_or_else(|| msg.ecu());
+        let header = msg.storage_header();
+        self.write_packet(ecu, header.seconds, header.microseconds, &msg.as_bytes()[STORAGE_HEADER_SIZE..])
+    }
+
+    // One message starting at its standard header
+    fn write_packet(&mut self, ecu: EcuId, seconds: u32, microseconds: u32, message: &[u8]) -> io::Result<()> {
+        let interface = self.interface(ecu)?;
+        let packet = match self.link_type {
+            DltPcapLinkType::Dlt => message.to_vec(),
+            DltPcapLinkType::Udp => self.udp_datagram(interface, message)?,
+        };
+
+        let time = seconds as u64 * 1_000_000 + microseconds as u64;
+        let mut body = Vec::with_capacity(20 + packet.len() + 3);
+        body.extend_from_slice(&interface.to_le_bytes());
+        body.extend_from_slice(&((time >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(time as u32).to_le_bytes());
+        body.extend_from_slice(&(packet.len() as u32).to_le_bytes()); // Captured
+        body.extend_from_slice(&(packet.len() as u32).to_le_bytes()); // Original
+        body.extend_from_slice(&packet);
+        pad32(&mut body);
+        write_block(&mut self.out, BLOCK_ENHANCED_PACKET, &body)?;
+        self.packets_written += 1;
+        Ok(())
+    }
+
+    // Interface of an ECU, described on first use
+    fn interface(&mut self, ecu: EcuId) -> io::Result<u32> {
+        if let Some(&id) = self.interfaces.get(&ecu) {
+            return Ok(id);
+        }
+        let id = self.interfaces.len() as u32;
+        let link_type = match self.link_type {
+            DltPcapLinkType::Udp => LINKTYPE_RAW,
+            DltPcapLinkType::Dlt => LINKTYPE_AUTOSAR_DLT,
+        };
+        let mut body = Vec::new();
+        body.extend_from_slice(&link_type.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.extend_from_slice(&SNAPLEN.to_le_bytes());
+        push_option(&mut body, OPT_IF_NAME, ecu.as_str().as_bytes());
+        push_option(&mut body, OPT_IF_TSRESOL, &[6]); // Microseconds
+        push_option(&mut body, OPT_END, &[]);
+        write_block(&mut self.out, BLOCK_INTERFACE_DESCRIPTION, &body)?;
+        self.interfaces.insert(ecu, id);
+        Ok(id)
+    }
+
+    fn udp_datagram(&mut self, interface: u32, message: &[u8]) -> io::Result<Vec<u8>> {
+        let total_len = IPV4_HEADER_SIZE + UDP_HEADER_SIZE + message.len();
+        if total_len > u16::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Message too large for a UDP datagram"));
+        }
+        let n = interface + 1;
+        let source = [10, 0, (n >> 8) as u8, n as u8];
+        self.ip_id = self.ip_id.wrapping_add(1);
+
+        let mut packet = Vec::with_capacity(total_len);
+        packet.extend_from_slice(&[0x45, 0]); // IPv4, 20 byte header
+        packet.extend_from_slice(&(total_len as u16).to_be_bytes());
+        packet.extend_from_slice(&self.ip_id.to_be_bytes());
+        packet.extend_from_slice(&0x4000u16.to_be_bytes()); // Don't fragment
+        packet.extend_from_slice(&[64, 17]); // TTL, UDP
+        packet.extend_from_slice(&[0, 0]); // Checksum, filled in below
+        packet.extend_from_slice(&source);
+        packet.extend_from_slice(&DLT_MULTICAST_ADDRESS);
+        let checksum = ipv4_checksum(&packet);
+        packet[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+        packet.extend_from_slice(&DLT_UDP_PORT.to_be_bytes());
+        packet.extend_from_slice(&DLT_UDP_PORT.to_be_bytes());
+        packet.extend_from_slice(&((UDP_HEADER_SIZE + message.len()) as u16).to_be_bytes());
+        packet.extend_from_slice(&[0, 0]); // No UDP checksum (optional over IPv4)
+        packet.extend_from_slice(message);
+        Ok(packet)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    pub fn packets_written(&self) -> u64 {
+        self.packets_written
+    }
+
+    // ECUs in interface order
+    pub fn interfaces(&self) -> Vec<EcuId> {
+        let mut ecus: Vec<_> = self.interfaces.iter().map(|(&ecu, &id)| (id, ecu)).collect();
+        ecus.sort();
+        ecus.into_iter().map(|(_, ecu)| ecu).collect()
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn write_block<W: Write>(out: &mut W, block_type: u32, body: &[u8]) -> io::Result<()> {
+    let total_len = (body.len() + 12) as u32;
+    out.write_all(&block_type.to_le_bytes())?;
+    out.write_all(&total_len.to_le_bytes())?;
+    out.write_all(body)?;
+    out.write_all(&total_len.to_le_bytes())
+}
+
+fn push_option(body: &mut Vec<u8>, code: u16, value: &[u8]) {
+    body.extend_from_slice(&code.to_le_bytes());
+    body.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    body.extend_from_slice(value);
+    pad32(body);
+}
+
+fn pad32(body: &mut Vec<u8>) {
+    body.resize(body.len().next_multiple_of(4), 0);
+}
+
+fn ipv4_checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = header.chunks(2).map(|w| u16::from_be_bytes([w[0], w[1]]) as u32).sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
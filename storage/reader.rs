@@ -0,0 +1,150 @@
+// DLT file reader: iterates the records of a .dlt file
+// Records are framed by DltFrameDecoder, so a corrupt record is skipped and reported
+// instead of ending the file. Version 2 records are converted to version 1.
+// Compressed files (.dlt.gz, .dlt.zst) are opened transparently.
+use crate::archive::DltFileSource;
+use dlt_core::{DltFrame, DltFrameDecoder, DltMessage, DltParseErrorStats, DltSkipped};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+// One message and where its storage header starts in the file
+#[derive(Debug, Clone)]
+pub struct DltFileRecord {
+    pub offset: u64,
+    pub message: DltMessage,
+}
+
+pub struct DltFileReader<R = DltFileSource> {
+    source: R,
+    buffer: Vec<u8>,
+    decoder: DltFrameDecoder,
+    base_offset: u64,           // File offset of decoder stream offset 0
+    eof: bool,
+    skipped: Vec<DltSkipped>,   // Not yet taken by the caller, file offsets
+    parse_error_stats: DltParseErrorStats,
+}
+
+impl DltFileReader<DltFileSource> {
+    // Plain or compressed file
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::from_reader(DltFileSource::open(path)?))
+    }
+}
+
+impl<R: Read> DltFileReader<R> {
+    // Records from any byte source, starting at offset 0
+    pub fn from_reader(source: R) -> Self {
+        DltFileReader {
+            source,
+            buffer: vec![0u8; READ_CHUNK_SIZE],
+            decoder: DltFrameDecoder::new(),
+            base_offset: 0,
+            eof: false,
+            skipped: Vec::new(),
+            parse_error_stats: DltParseErrorStats::default(),
+        }
+    }
+
+    // Next record; None at end of file
+    pub fn next_record(&mut self) -> io::Result<Option<DltFileRecord>> {
+        loop {
+            let message = match self.decoder.next_frame() {
+                Some(DltFrame::Message(msg)) => msg.to_message(),
+                // The frame is consumed, so reading can go on after this error
+                Some(DltFrame::MessageV2(msg)) => msg.to_v1().map_err(|e| {
+                    let offset = self.base_offset + self.decoder.frame_offset();
+                    io::Error::new(io::ErrorKind::InvalidData, format!("Message at offset {}: {}", offset, e))
+                })?,
+                Some(DltFrame::Skipped(skipped)) => {
+                    self.record_skipped(skipped);
+                    continue;
+                }
+                None if self.eof => {
+                    // A record cut off at the end (e.g. a file still being written)
+                    if let Some(skipped) = self.decoder.finish() {
+                        self.record_skipped(skipped);
+                    }
+                    return Ok(None);
+                }
+                None => {
+                    let n = self.source.read(&mut self.buffer)?;
+                    self.decoder.push(&self.buffer[..n]);
+                    self.eof = n == 0;
+                    continue;
+                }
+            };
+            let offset = self.base_offset + self.decoder.frame_offset();
+            return Ok(Some(DltFileRecord { offset, message }));
+        }
+    }
+
+    fn record_skipped(&mut self, mut skipped: DltSkipped) {
+        skipped.offset += self.base_offset;
+        self.parse_error_stats.record(&skipped.reason);
+        self.skipped.push(skipped);
+    }
+
+    // File offset of the next unread record
+    pub fn next_offset(&self) -> u64 {
+        self.base_offset + self.decoder.stream_offset()
+    }
+
+    // Byte ranges dropped while resynchronizing since the last call
+    pub fn take_skipped(&mut self) -> Vec<DltSkipped> {
+        std::mem::take(&mut self.skipped)
+    }
+
+    // Framing error counters (one per skipped range)
+    pub fn parse_error_stats(&self) -> &DltParseErrorStats {
+        &self.parse_error_stats
+    }
+
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+}
+
+impl<R: Read + Seek> DltFileReader<R> {
+    // Continue reading at a file offset, normally one returned in a DltFileRecord
+    // An offset inside a record resynchronizes to the next one.
+    pub fn seek(&mut self, offset: u64) -> io::Result<()> {
+        self.source.seek(SeekFrom::Start(offset))?;
+        self.decoder = DltFrameDecoder::new();
+        self.base_offset = offset;
+        self.eof = false;
+        Ok(())
+    }
+
+    // Record at a file offset
+    pub fn read_at(&mut self, offset: u64) -> io::Result<Option<DltFileRecord>> {
+        self.seek(offset)?;
+        self.next_record()
+    }
+
+    // Offsets of all records, by scanning the whole file
+    // The read position and the skipped-range reports are left as they were.
+    pub fn record_offsets(&mut self) -> io::Result<Vec<u64>> {
+        let position = self.next_offset();
+        let skipped = self.skipped.len();
+        let stats = self.parse_error_stats.clone();
+        self.seek(0)?;
+        let mut offsets = Vec::new();
+        while let Some(record) = self.next_record()? {
+            offsets.push(record.offset);
+        }
+        self.skipped.truncate(skipped);
+        self.parse_error_stats = stats;
+        self.seek(position)?;
+        Ok(offsets)
+    }
+}
+
+impl<R: Read> Iterator for DltFileReader<R> {
+    type Item = io::Result<DltFileRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
@@ -0,0 +1,116 @@
+// DLT file writer: appends storage-header records to a .dlt file
+use dlt_core::{DltMessage, DltMessageRef, DltMessageV2, DLT_STORAGE_HEADER_PATTERN};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_WRITE_BUFFER_SIZE: usize = 64 * 1024;
+
+// When written records are forced to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DltSyncMode {
+    #[default]
+    Never,        // Left to the OS (records may be lost on power failure)
+    OnFlush,      // fsync on flush() and when the writer is dropped
+    EveryMessage, // Flush and fsync after each record
+}
+
+pub struct DltFileWriter {
+    file: BufWriter<File>,
+    path: PathBuf,
+    sync: DltSyncMode,
+    messages_written: u64,
+    size: u64, // File size including existing records when appending
+}
+
+impl DltFileWriter {
+    // Create or truncate a file
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::create(path.as_ref())?;
+        Ok(Self::from_file(file, path.as_ref(), 0))
+    }
+
+    // Open a file for appending, creating it if missing
+    pub fn append<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path.as_ref())?;
+        let size = file.metadata()?.len();
+        Ok(Self::from_file(file, path.as_ref(), size))
+    }
+
+    fn from_file(file: File, path: &Path, size: u64) -> Self {
+        DltFileWriter {
+            file: BufWriter::with_capacity(DEFAULT_WRITE_BUFFER_SIZE, file),
+            path: path.to_path_buf(),
+            sync: DltSyncMode::default(),
+            messages_written: 0,
+            size,
+        }
+    }
+
+    pub fn with_sync(mut self, sync: DltSyncMode) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    pub fn write_message(&mut self, msg: &DltMessage) -> io::Result<()> {
+        self.write_record(&msg.to_bytes())
+    }
+
+    pub fn write_message_v2(&mut self, msg: &DltMessageV2) -> io::Result<()> {
+        let record = msg.to_bytes().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        self.write_record(&record)
+    }
+
+    // Copy a received record without re-encoding it
+    pub fn write_message_ref(&mut self, msg: &DltMessageRef) -> io::Result<()> {
+        self.write_record(msg.as_bytes())
+    }
+
+    // A complete record starting with its storage header
+    pub fn write_record(&mut self, record: &[u8]) -> io::Result<()> {
+        if !record.starts_with(&DLT_STORAGE_HEADER_PATTERN) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Record does not start with a storage header"));
+        }
+        self.file.write_all(record)?;
+        self.messages_written += 1;
+        self.size += record.len() as u64;
+        if self.sync == DltSyncMode::EveryMessage {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    // Hand buffered records to the OS (and fsync unless the mode is Never)
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.sync {
+            DltSyncMode::Never => self.file.flush(),
+            DltSyncMode::OnFlush | DltSyncMode::EveryMessage => self.sync(),
+        }
+    }
+
+    // Flush and fsync regardless of the sync mode
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file.get_ref().sync_data()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Records written through this writer
+    pub fn messages_written(&self) -> u64 {
+        self.messages_written
+    }
+
+    // File size once buffered records are flushed
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Drop for DltFileWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
@@ -2,77 +2,84 @@
 # Continuous logging test to detect message loss
 
 echo "=== DLT Continuous Logging Test ==="
-echo "This will send 1000 messages and check for gaps"
+echo "This will send 1000 messages and check for gaps, through one user buffer and"
+echo "then spread over four (one context's messages arrive out of order)"
 echo ""
 
-# Kill any existing daemons
-killall -9 dlt-daemon 2>/dev/null
-sleep 1
+FAILED=0
 
-# Start our daemon
-echo "Starting dlt-daemon..."
-./target/release/dlt-daemon &
-DAEMON_PID=$!
-sleep 2
+# run_case <name> <message> <dlt-example-user options...>
+run_case() {
+    local name=$1 message=$2
+    shift 2
 
-# Send logs with sequential numbering
-echo "Sending 1000 messages..."
-./target/release/dlt-example-user -n 1000 -d 5 TestMsg > /tmp/dlt-send.log 2>&1
+    echo "=== Case: $name ==="
 
-# Give time for all messages to be processed
-sleep 2
+    # Kill any existing daemons
+    killall -9 dlt-daemon 2>/dev/null
+    sleep 1
 
-echo ""
-echo "=== Checking for message gaps ==="
+    # Start our daemon
+    echo "Starting dlt-daemon..."
+    ./target/release/dlt-daemon 2> /tmp/dlt-daemon-err.log &
+    DAEMON_PID=$!
+    sleep 2
 
-# Start receiver in background and capture output
-timeout 5 ./target/release/dlt-receive -a localhost > /tmp/dlt-receive.log 2>&1 &
-RECV_PID=$!
+    # Send logs with sequential numbering
+    echo "Sending 1000 messages..."
+    ./target/release/dlt-example-user -n 1000 "$@" $message > /tmp/dlt-send.log 2>&1
 
-# Wait for receiver to finish or timeout
-sleep 6
+    # Give time for all messages to be processed
+    sleep 2
 
-# Analyze the received messages
-echo ""
-echo "=== Analysis ==="
-if [ -f /tmp/dlt-receive.log ]; then
-    # Extract message numbers
-    grep "TestMsg" /tmp/dlt-receive.log | awk '{print $NF}' | grep -oE '[0-9]+' | sort -n > /tmp/dlt-numbers.txt
+    echo ""
+    echo "=== Checking for message gaps ==="
+
+    # Receive until the daemon has nothing left for 3 seconds
+    ./target/release/dlt-receive -a localhost -t 3 > /tmp/dlt-receive.log 2> /tmp/dlt-receive-err.log
 
-    TOTAL=$(wc -l < /tmp/dlt-numbers.txt)
+    # Gaps are detected by dlt-receive from the per-context message counters
+    echo ""
+    echo "=== Analysis ==="
+    TOTAL=$(grep -c "$message" /tmp/dlt-receive.log)
     echo "Total messages received: $TOTAL / 1000"
 
-    # Check for gaps
     echo ""
-    echo "Checking for gaps in sequence..."
-    python3 << 'EOF'
-with open('/tmp/dlt-numbers.txt') as f:
-    numbers = [int(line.strip()) for line in f if line.strip()]
-
-if not numbers:
-    print("No messages received!")
-else:
-    gaps = []
-    for i in range(len(numbers) - 1):
-        diff = numbers[i+1] - numbers[i]
-        if diff > 1:
-            gaps.append((numbers[i], numbers[i+1]))
-
-    if gaps:
-        print(f"Found {len(gaps)} gaps:")
-        for start, end in gaps[:10]:  # Show first 10 gaps
-            print(f"  Gap: {start} -> {end} (missing {end - start - 1} messages)")
-    else:
-        print("No gaps found! All messages received in sequence.")
-
-    print(f"\nFirst message: {numbers[0]}")
-    print(f"Last message: {numbers[-1]}")
-EOF
-else
-    echo "ERROR: No receiver output found"
-fi
+    grep "^Counters" /tmp/dlt-receive-err.log
+    GAPS=$(grep -c "^Message counter gap" /tmp/dlt-receive-err.log)
+    if [ "$GAPS" -gt 0 ]; then
+        echo "Found $GAPS gaps:"
+        grep "^Message counter gap" /tmp/dlt-receive-err.log | head -10
+        grep "^Lost messages" /tmp/dlt-receive-err.log
+    else
+        echo "No gaps found! All messages received in sequence."
+    fi
 
-# Cleanup
-kill $DAEMON_PID 2>/dev/null
-echo ""
+    # Every message that did not arrive must be reported lost, and only those
+    LOST=$(grep "^Lost messages" /tmp/dlt-receive-err.log | awk '{print $3}')
+    if [ $((TOTAL + ${LOST:-0})) -ne 1000 ]; then
+        echo "FAIL: $TOTAL received + ${LOST:-0} reported lost != 1000 sent"
+        FAILED=1
+    fi
+    DAEMON_GAPS=$(grep -c "^Message counter gap" /tmp/dlt-daemon-err.log)
+    if [ "$TOTAL" -eq 1000 ] && [ "$DAEMON_GAPS" -gt 0 ]; then
+        echo "FAIL: daemon reported $DAEMON_GAPS gaps although nothing was lost"
+        FAILED=1
+    fi
+
+    # Cleanup
+    kill $DAEMON_PID 2>/dev/null
+    wait $DAEMON_PID 2>/dev/null
+    echo ""
+}
+
+run_case "one buffer" TestMsg -d 5
+
+# Levels map to different buffers, each sent by its own worker and connection
+DLT_USER_NUM_BUFFERS=4 run_case "four buffers" MultiMsg -d 0 -m
+
+if [ $FAILED -ne 0 ]; then
+    echo "Test FAILED. Logs saved to /tmp/dlt-*.log"
+    exit 1
+fi
 echo "Test complete. Logs saved to /tmp/dlt-*.log"
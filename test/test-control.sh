@@ -0,0 +1,73 @@
+#!/bin/bash
+# Control request test: dlt-control against the daemon (log info, log levels, version)
+
+echo "=== DLT Control Test ==="
+
+killall -9 dlt-daemon 2>/dev/null
+sleep 1
+
+echo "Starting dlt-daemon (no history, so clients only see new messages)..."
+./target/release/dlt-daemon -r 0 > /tmp/dlt-control-daemon.log 2>&1 &
+DAEMON_PID=$!
+sleep 2
+
+FAILED=0
+
+# check <description> <expected output pattern> <dlt-control options...>
+check() {
+    local what=$1 pattern=$2
+    shift 2
+    local output
+    output=$(./target/release/dlt-control -t 2000 "$@" localhost 2>&1)
+    if echo "$output" | grep -q -- "$pattern"; then
+        echo "OK: $what"
+    else
+        echo "FAIL: $what, expected '$pattern', got:"
+        echo "$output"
+        FAILED=1
+    fi
+}
+
+# Contexts become known to the daemon through their messages
+./target/release/dlt-example-user -A LOG1 -C CTX1 -l 4 -n 1 -d 0 Hello > /dev/null 2>&1
+./target/release/dlt-example-user -A LOG2 -C CTX2 -l 4 -n 1 -d 0 Hello > /dev/null 2>&1
+
+check "software version" "\[get_software_version ok\] dlt-daemon" -V
+check "log info of all contexts" "\[get_log_info 7\] LOG1 CTX1:-1 LOG2 CTX2:-1" -j
+# IDs given after the request option still apply
+check "set log level" "\[set_log_level ok\]" -l 2 -a LOG1 -c CTX1
+check "log info of one context" "\[get_log_info 7\] LOG1 CTX1:2$" -j -a LOG1
+check "log info without a match" "\[get_log_info 8\]" -a NONE -j
+check "invalid log level" "\[set_log_level error\]" -a LOG1 -c CTX1 -l 9
+check "set default log level" "\[set_default_log_level ok\]" -d 3
+check "store config" "\[store_config not_supported\]" -o
+
+# LOG1 CTX1 now passes errors only, LOG2 CTX2 warnings (default level) and above
+./target/release/dlt-receive -a localhost -t 3 > /tmp/dlt-control-receive.log 2>&1 &
+RECEIVE_PID=$!
+sleep 1
+./target/release/dlt-example-user -A LOG1 -C CTX1 -l 4 -n 5 -d 0 Filtered > /dev/null 2>&1
+./target/release/dlt-example-user -A LOG1 -C CTX1 -l 2 -n 5 -d 0 Passed > /dev/null 2>&1
+./target/release/dlt-example-user -A LOG2 -C CTX2 -l 3 -n 5 -d 0 Passed > /dev/null 2>&1
+./target/release/dlt-example-user -A LOG2 -C CTX2 -l 5 -n 5 -d 0 Filtered > /dev/null 2>&1
+wait $RECEIVE_PID
+
+PASSED=$(grep -c "Passed" /tmp/dlt-control-receive.log)
+FILTERED=$(grep -c "Filtered" /tmp/dlt-control-receive.log)
+echo "Messages above the log level: $FILTERED (expected 0), others: $PASSED / 10"
+if [ "$FILTERED" -ne 0 ] || [ "$PASSED" -ne 10 ]; then
+    FAILED=1
+fi
+
+check "reset to factory default" "\[reset_to_factory_default ok\]" -g
+check "levels back to default" "\[get_log_info 7\] LOG1 CTX1:-1 LOG2 CTX2:-1" -j
+
+kill $DAEMON_PID
+wait $DAEMON_PID 2>/dev/null
+
+echo ""
+if [ $FAILED -ne 0 ]; then
+    echo "Test FAILED. Logs saved to /tmp/dlt-control-*.log"
+    exit 1
+fi
+echo "Test passed."
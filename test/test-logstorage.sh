@@ -0,0 +1,122 @@
+#!/bin/bash
+# Offline log storage test: filters, rotation, the file limit and compression
+
+echo "=== DLT Offline Log Storage Test ==="
+
+STORAGE_DIR=/tmp/dlt-logstorage
+rm -rf $STORAGE_DIR
+mkdir -p $STORAGE_DIR
+
+# All messages of LOG1 in small rotating files, errors of any application in one file
+cat > $STORAGE_DIR/dlt_logstorage.conf <<CONF
+[FILTER1]
+LogAppName=LOG1
+ContextName=.*
+LogLevel=DLT_LOG_VERBOSE
+File=all
+FileSize=20000
+NOFiles=3
+
+[FILTER2]
+LogAppName=.*
+ContextName=.*
+LogLevel=DLT_LOG_ERROR
+File=errors
+FileSize=1000000
+NOFiles=1
+SyncBehavior=ON_DAEMON_EXIT
+
+[FILTER3]
+LogAppName=LOG1
+ContextName=.*
+LogLevel=DLT_LOG_INFO
+File=archive
+FileSize=20000
+NOFiles=10
+Compression=zstd
+CONF
+
+killall -9 dlt-daemon 2>/dev/null
+sleep 1
+
+echo "Starting dlt-daemon with log storage (no client connected)..."
+./target/release/dlt-daemon -l $STORAGE_DIR &
+DAEMON_PID=$!
+sleep 2
+
+./target/release/dlt-example-user -A LOG1 -l 4 -n 2000 -d 1 InfoMsg > /dev/null 2>&1
+./target/release/dlt-example-user -A LOG2 -l 2 -n 50 -d 1 ErrorMsg > /dev/null 2>&1
+./target/release/dlt-example-user -A LOG2 -l 4 -n 50 -d 1 DroppedMsg > /dev/null 2>&1
+sleep 1
+
+# SIGTERM syncs ON_DAEMON_EXIT files
+kill $DAEMON_PID
+wait $DAEMON_PID 2>/dev/null
+
+echo ""
+echo "=== Files ==="
+ls -l $STORAGE_DIR
+
+FAILED=0
+ALL_FILES=$(ls $STORAGE_DIR/all_*.dlt 2>/dev/null | wc -l)
+if [ "$ALL_FILES" -ne 3 ]; then
+    echo "FAIL: expected 3 rotated files for FILTER1, found $ALL_FILES"
+    FAILED=1
+fi
+if ! ls $STORAGE_DIR/all_001_*.dlt > /dev/null 2>&1; then
+    echo "OK: oldest FILTER1 files were deleted"
+else
+    echo "FAIL: all_001 should have been deleted"
+    FAILED=1
+fi
+
+# Full files are compressed, the open one is left as it is
+ARCHIVES=$(ls $STORAGE_DIR/archive_*.dlt.zst 2>/dev/null | wc -l)
+echo "FILTER3 compressed files: $ARCHIVES"
+if [ "$ARCHIVES" -lt 2 ]; then
+    echo "FAIL: expected compressed archive_*.dlt.zst files"
+    FAILED=1
+fi
+
+ERRORS=$(cat $STORAGE_DIR/errors_*.dlt | grep -a -o "ErrorMsg" | wc -l)
+DROPPED=$(cat $STORAGE_DIR/*.dlt | grep -a -o "DroppedMsg" | wc -l)
+echo "FILTER2 error messages: $ERRORS / 50, messages below the filter levels: $DROPPED"
+if [ "$ERRORS" -ne 50 ] || [ "$DROPPED" -ne 0 ]; then
+    FAILED=1
+fi
+
+# Two filters writing the same files are rejected
+BAD_DIR=/tmp/dlt-logstorage-bad
+rm -rf $BAD_DIR
+mkdir -p $BAD_DIR
+cat > $BAD_DIR/dlt_logstorage.conf <<CONF
+[FILTER1]
+LogAppName=LOG1
+ContextName=.*
+LogLevel=DLT_LOG_INFO
+File=shared
+FileSize=20000
+NOFiles=3
+
+[FILTER2]
+LogAppName=LOG2
+ContextName=.*
+LogLevel=DLT_LOG_INFO
+File=shared
+FileSize=20000
+NOFiles=3
+CONF
+if timeout 5 ./target/release/dlt-daemon -l $BAD_DIR 2>&1 | grep -q "uses File 'shared'"; then
+    echo "OK: duplicate File name rejected"
+else
+    echo "FAIL: a config with a duplicate File name was accepted"
+    FAILED=1
+fi
+
+echo ""
+if [ "$FAILED" -eq 0 ]; then
+    echo "Test passed."
+else
+    echo "Test FAILED."
+    exit 1
+fi
@@ -0,0 +1,24 @@
+#!/bin/bash
+# no_std build check of dlt-core (default features off: no_std + alloc)
+# Builds for a bare-metal Cortex-M target, where any use of std fails to compile.
+
+TARGET=${1:-thumbv7em-none-eabihf}
+
+echo "=== dlt-core no_std Build Check ($TARGET) ==="
+
+if ! rustup target list --installed | grep -qx "$TARGET"; then
+    echo "Installing target $TARGET..."
+    rustup target add "$TARGET" || exit 1
+fi
+
+if ! cargo build -p dlt-core --no-default-features --target "$TARGET"; then
+    echo "FAIL: dlt-core does not build without std for $TARGET"
+    exit 1
+fi
+
+if ! cargo clippy -p dlt-core --no-default-features --target "$TARGET" -- -D warnings; then
+    echo "FAIL: clippy warnings in the no_std build"
+    exit 1
+fi
+
+echo "PASS: dlt-core builds as no_std + alloc for $TARGET"
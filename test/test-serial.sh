@@ -0,0 +1,40 @@
+#!/bin/bash
+# Serial header (DLS\x01) test over a pseudo-terminal pair
+# Requires socat to create the linked ptys.
+
+echo "=== DLT Serial Framing Test ==="
+
+if ! command -v socat > /dev/null; then
+    echo "ERROR: socat not found (needed to create a pty pair)"
+    exit 1
+fi
+
+TTY_ECU=/tmp/dlt-tty-ecu
+TTY_HOST=/tmp/dlt-tty-host
+
+# Linked ptys: what the "ECU" writes to one end is read from the other
+socat pty,raw,echo=0,link=$TTY_ECU pty,raw,echo=0,link=$TTY_HOST &
+SOCAT_PID=$!
+sleep 1
+
+# Receive on the host side
+./target/release/dlt-receive -s $TTY_HOST > /tmp/dlt-serial-receive.log 2>&1 &
+RECV_PID=$!
+sleep 1
+
+# Emulate the ECU UART
+echo "Sending 100 messages with serial headers..."
+./target/release/dlt-example-user -s $TTY_ECU -n 100 -d 5 SerialMsg > /tmp/dlt-serial-send.log 2>&1
+
+sleep 1
+kill $RECV_PID 2>/dev/null
+kill $SOCAT_PID 2>/dev/null
+
+echo ""
+echo "=== Analysis ==="
+TOTAL=$(grep -c "SerialMsg" /tmp/dlt-serial-receive.log)
+echo "Total messages received: $TOTAL / 100"
+grep "Resync" /tmp/dlt-serial-receive.log
+
+echo ""
+echo "Test complete. Logs saved to /tmp/dlt-serial-*.log"
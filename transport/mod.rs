@@ -2,7 +2,9 @@
 pub mod traits;
 pub mod unix;
 pub mod tcp;
+pub mod serial;
 
 pub use traits::*;
 pub use unix::*;
 pub use tcp::*;
+pub use serial::*;
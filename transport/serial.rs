@@ -0,0 +1,165 @@
+// Serial (tty/pty) transport: raw bytes over a UART or pseudo-terminal
+// Framing with the "DLS\x01" serial header is done by dlt-core
+// (DltMessage::to_serial_bytes, DltFrameDecoder::with_serial_header).
+use crate::traits::Transport;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Result, Write};
+use std::os::fd::{AsFd, AsRawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::time::Duration;
+use nix::fcntl::{fcntl, FcntlArg, FdFlag};
+use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
+use nix::pty::openpty;
+use nix::sys::termios::{self, BaudRate, SetArg};
+
+pub const DLT_SERIAL_DEFAULT_BAUDRATE: u32 = 115200;
+
+pub struct SerialTransport {
+    device_path: String,
+    baudrate: u32,
+    file: Option<File>,
+    read_timeout: PollTimeout, // NONE blocks indefinitely
+}
+
+impl SerialTransport {
+    pub fn new(device_path: &str, baudrate: u32) -> Self {
+        SerialTransport {
+            device_path: device_path.to_string(),
+            baudrate,
+            file: None,
+            read_timeout: PollTimeout::NONE,
+        }
+    }
+
+    /// Connected pseudo-terminal pair for local testing: (master, slave)
+    /// Bytes written to one end are read from the other, like a null-modem cable.
+    pub fn pty_pair() -> Result<(SerialTransport, SerialTransport)> {
+        let pty = openpty(None, None).map_err(std::io::Error::other)?;
+        // openpty does not set close-on-exec like std does for its own files
+        for fd in [&pty.master, &pty.slave] {
+            fcntl(fd.as_raw_fd(), FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC)).map_err(std::io::Error::other)?;
+        }
+        let slave_path = nix::unistd::ttyname(&pty.slave)
+            .map_err(std::io::Error::other)?
+            .to_string_lossy()
+            .into_owned();
+
+        let slave = File::from(pty.slave);
+        set_raw(&slave, None)?;
+        let master = SerialTransport {
+            device_path: String::new(),
+            baudrate: DLT_SERIAL_DEFAULT_BAUDRATE,
+            file: Some(File::from(pty.master)),
+            read_timeout: PollTimeout::NONE,
+        };
+        let slave = SerialTransport {
+            device_path: slave_path,
+            baudrate: DLT_SERIAL_DEFAULT_BAUDRATE,
+            file: Some(slave),
+            read_timeout: PollTimeout::NONE,
+        };
+        Ok((master, slave))
+    }
+
+    /// Device path (for a pty pair: empty for the master, /dev/pts/N for the slave)
+    pub fn device_path(&self) -> &str {
+        &self.device_path
+    }
+}
+
+impl Transport for SerialTransport {
+    fn send(&mut self, data: &[u8]) -> Result<usize> {
+        if let Some(ref mut file) = self.file {
+            file.write_all(data)?;
+            Ok(data.len())
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "Not connected",
+            ))
+        }
+    }
+
+    fn receive(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if let Some(ref mut file) = self.file {
+            if self.read_timeout.is_some() {
+                let mut fds = [PollFd::new(file.as_fd(), PollFlags::POLLIN)];
+                if poll(&mut fds, self.read_timeout).map_err(std::io::Error::from)? == 0 {
+                    return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "Read timed out"));
+                }
+            }
+            match file.read(buf) {
+                // A pty reports EIO once the other end is closed
+                Err(e) if e.raw_os_error() == Some(libc::EIO) => Ok(0),
+                result => result,
+            }
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "Not connected",
+            ))
+        }
+    }
+
+    fn connect(&mut self) -> Result<()> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(&self.device_path)?;
+        set_raw(&file, Some(self.baudrate))?;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        self.file = None;
+        Ok(())
+    }
+
+    // Zero is rejected like for sockets; longer timeouts are rounded to whole
+    // milliseconds, at most poll's limit (about 24 days)
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.read_timeout = match timeout {
+            None => PollTimeout::NONE,
+            Some(timeout) if timeout.is_zero() => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Zero read timeout",
+                ))
+            }
+            Some(timeout) => PollTimeout::try_from(timeout.as_millis().max(1)).unwrap_or(PollTimeout::MAX),
+        };
+        Ok(())
+    }
+}
+
+// Raw 8N1 mode: no echo, no line editing, no character translation
+fn set_raw<Fd: AsFd>(fd: Fd, baudrate: Option<u32>) -> Result<()> {
+    let mut tio = termios::tcgetattr(&fd).map_err(std::io::Error::other)?;
+    termios::cfmakeraw(&mut tio);
+    if let Some(baudrate) = baudrate {
+        termios::cfsetspeed(&mut tio, baud_rate(baudrate)?).map_err(std::io::Error::other)?;
+    }
+    termios::tcsetattr(&fd, SetArg::TCSANOW, &tio).map_err(std::io::Error::other)
+}
+
+fn baud_rate(baudrate: u32) -> Result<BaudRate> {
+    let rate = match baudrate {
+        9600 => BaudRate::B9600,
+        19200 => BaudRate::B19200,
+        38400 => BaudRate::B38400,
+        57600 => BaudRate::B57600,
+        115200 => BaudRate::B115200,
+        230400 => BaudRate::B230400,
+        460800 => BaudRate::B460800,
+        921600 => BaudRate::B921600,
+        _ => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Unsupported baud rate {}", baudrate),
+            ))
+        }
+    };
+    Ok(rate)
+}
@@ -2,6 +2,7 @@
 use crate::traits::Transport;
 use std::io::{Result, Read, Write};
 use std::net::TcpStream;
+use std::time::Duration;
 
 pub struct TcpTransport {
     address: String,
@@ -50,4 +51,11 @@ impl Transport for TcpTransport {
         self.stream = None;
         Ok(())
     }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        if let Some(ref stream) = self.stream {
+            stream.set_read_timeout(timeout)?;
+        }
+        Ok(())
+    }
 }
@@ -1,11 +1,17 @@
 // Transport abstraction - allows pluggable communication backends
 use std::io::Result;
+use std::time::Duration;
 
 pub trait Transport: Send {
     fn send(&mut self, data: &[u8]) -> Result<usize>;
     fn receive(&mut self, buf: &mut [u8]) -> Result<usize>;
     fn connect(&mut self) -> Result<()>;
     fn disconnect(&mut self) -> Result<()>;
+
+    // Bound how long receive() may block; None blocks indefinitely
+    fn set_read_timeout(&mut self, _timeout: Option<Duration>) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub trait TransportListener: Send {
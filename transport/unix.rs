@@ -35,7 +35,7 @@ impl UnixSocketTransport {
     pub fn set_send_buffer_size(&self, size: usize) -> Result<()> {
         if let Some(ref stream) = self.stream {
             setsockopt(stream, sockopt::SndBuf, &size)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+                .map_err(std::io::Error::other)?;
         }
         Ok(())
     }
@@ -1,6 +1,7 @@
 // dlt-example-user: example binary using user library
 // Rust equivalent of dlt-daemon/src/examples/dlt-example-user.c
-use dlt_user::{DltContext, DltLogLevel, dlt_enable_local_print};
+use dlt_user::{DltContext, DltLogLevel, NetworkTraceType, dlt_enable_local_print};
+use dlt_user::{AppId, ContextId, DltMessage, EcuId, SerialTransport, Transport, DLT_SERIAL_DEFAULT_BAUDRATE};
 use std::env;
 
 fn usage() {
@@ -11,8 +12,11 @@ fn usage() {
     println!("  -n count      Number of messages to be generated (Default: 10)");
     println!("  -a            Enable local printing of DLT messages (Default: disabled)");
     println!("  -l level      Set log level (1=Fatal, 2=Error, 3=Warn, 4=Info, 5=Debug, 6=Verbose) (Default: 3=Warn)");
+    println!("  -m            Cycle the log level through Fatal..Verbose (ignores -l)");
     println!("  -A AppID      Set app ID for send message (Default: LOG)");
     println!("  -C ContextID  Set context ID for send message (Default: TEST)");
+    println!("  -s tty        Write messages with serial headers to a tty instead of the daemon");
+    println!("  -t size       Also send count Ethernet network traces with size payload bytes (segmented above 64 KiB)");
 }
 
 fn main() {
@@ -22,9 +26,12 @@ fn main() {
     let mut message = String::new();
     let mut delay = 500;
     let mut aflag = false;
+    let mut cycle_levels = false;
     let mut level = DltLogLevel::Warn;
     let mut app_id = "LOG";
     let mut context_id = "TEST";
+    let mut serial_device: Option<String> = None;
+    let mut trace_size: Option<usize> = None;
 
     // Parse arguments
     let mut i = 1;
@@ -67,6 +74,10 @@ fn main() {
                     i += 1;
                 }
             }
+            "-m" => {
+                cycle_levels = true;
+                i += 1;
+            }
             "-A" => {
                 if i + 1 < args.len() {
                     app_id = Box::leak(args[i + 1].clone().into_boxed_str());
@@ -83,6 +94,14 @@ fn main() {
                     i += 1;
                 }
             }
+            "-s" if i + 1 < args.len() => {
+                serial_device = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-t" if i + 1 < args.len() => {
+                trace_size = args[i + 1].parse().ok();
+                i += 2;
+            }
             "-h" | "--help" => {
                 usage();
                 return;
@@ -100,18 +119,55 @@ fn main() {
         std::process::exit(1);
     }
 
+    // Reject IDs that would otherwise be truncated or mangled
+    let app_id: AppId = app_id.parse().unwrap_or_else(|e| {
+        eprintln!("ERROR: Invalid app ID '{}': {}", app_id, e);
+        std::process::exit(1);
+    });
+    let context_id: ContextId = context_id.parse().unwrap_or_else(|e| {
+        eprintln!("ERROR: Invalid context ID '{}': {}", context_id, e);
+        std::process::exit(1);
+    });
+
+    if let Some(device) = serial_device {
+        if let Err(e) = send_serial(&device, app_id, context_id, &message, num_messages, delay as u64, level) {
+            eprintln!("Failed to write to {}: {}", device, e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     if aflag {
         dlt_enable_local_print();
     }
 
     // Register app and context (equivalent to DLT_REGISTER_APP + DLT_REGISTER_CONTEXT)
-    let ctx = DltContext::new(app_id, context_id, "Test Application for Logging", "Test Context for Logging");
+    let ctx = DltContext::from_ids(app_id, context_id);
 
     // Send log messages (equivalent to DLT_LOG loop in C version)
-    if let Err(e) = ctx.log_multiple(&message, num_messages, delay as u64, level) {
+    let result = if cycle_levels {
+        log_cycling_levels(&ctx, &message, num_messages, delay as u64)
+    } else {
+        ctx.log_multiple(&message, num_messages, delay as u64, level)
+    };
+    if let Err(e) = result {
         eprintln!("Failed to send logs: {}", e);
     }
 
+    if let Some(size) = trace_size {
+        // Ethernet header followed by a counting payload
+        let header = [0xffu8, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x08, 0x00];
+        let payload: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        for num in 0..num_messages {
+            println!("Send network trace {} ({} bytes)", num, size);
+            if let Err(e) = ctx.trace_network(NetworkTraceType::Ethernet, &header, &payload) {
+                eprintln!("Failed to send network trace: {}", e);
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(delay as u64));
+        }
+    }
+
     // Sleep before exit to ensure all messages are sent
     std::thread::sleep(std::time::Duration::from_secs(1));
 
@@ -125,3 +181,42 @@ fn main() {
 
     // Auto-unregisters on drop (equivalent to DLT_UNREGISTER_CONTEXT + DLT_UNREGISTER_APP)
 }
+
+// Emulate an ECU UART: each message goes out with a "DLS\x01" serial header
+fn send_serial(device: &str, app_id: AppId, context_id: ContextId, message: &str, count: usize, delay_ms: u64, level: DltLogLevel) -> std::io::Result<()> {
+    let mut serial = SerialTransport::new(device, DLT_SERIAL_DEFAULT_BAUDRATE);
+    serial.connect()?;
+    for num in 0..count {
+        println!("Send {} {}", num, message);
+        let mut msg = DltMessage::new_verbose(EcuId::new_const("ECU1"), app_id, context_id, &format!("{} {}", num, message))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?
+            .with_log_level(level.into());
+        msg.standard_header.mcnt = num as u8;
+        serial.send(&msg.to_serial_bytes())?;
+        if delay_ms > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+        }
+    }
+    Ok(())
+}
+
+// Like DltContext::log_multiple, with the level going Fatal, Error, ... Verbose and
+// around again
+fn log_cycling_levels(ctx: &DltContext, message: &str, count: usize, delay_ms: u64) -> std::io::Result<()> {
+    const LEVELS: [DltLogLevel; 6] = [
+        DltLogLevel::Fatal,
+        DltLogLevel::Error,
+        DltLogLevel::Warn,
+        DltLogLevel::Info,
+        DltLogLevel::Debug,
+        DltLogLevel::Verbose,
+    ];
+    for num in 0..count {
+        println!("Send {} {}", num, message);
+        ctx.log(LEVELS[num % LEVELS.len()], num as i32, message)?;
+        if delay_ms > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+        }
+    }
+    Ok(())
+}
@@ -9,7 +9,7 @@ fn main() {
 
     // Test 1: Single context logging
     {
-        let ctx = DltContext::new("TST1", "CTX1", "Test App 1", "Context 1");
+        let ctx = DltContext::new("TST1", "CTX1", "Test App 1", "Context 1").expect("Failed to register context");
         for i in 0..5 {
             ctx.log(&format!("Test message {}", i)).unwrap();
         }
@@ -19,8 +19,8 @@ fn main() {
 
     // Test 2: Multiple contexts
     {
-        let ctx1 = DltContext::new("TST2", "CTX1", "Test App 2", "Context 1");
-        let ctx2 = DltContext::new("TST2", "CTX2", "Test App 2", "Context 2");
+        let ctx1 = DltContext::new("TST2", "CTX1", "Test App 2", "Context 1").expect("Failed to register context");
+        let ctx2 = DltContext::new("TST2", "CTX2", "Test App 2", "Context 2").expect("Failed to register context");
 
         ctx1.log("Message from context 1").unwrap();
         ctx2.log("Message from context 2").unwrap();
@@ -30,11 +30,12 @@ fn main() {
 
     // Test 3: Concurrent logging
     {
-        let ctx = DltContext::new("TST3", "CONC", "Test App 3", "Concurrent Context");
+        let ctx = DltContext::new("TST3", "CONC", "Test App 3", "Concurrent Context").expect("Failed to register context");
 
         let handles: Vec<_> = (0..4).map(|thread_id| {
             thread::spawn(move || {
-                let local_ctx = DltContext::new("TST3", &format!("CT{}", thread_id), "Test App 3", &format!("Thread {}", thread_id));
+                let local_ctx = DltContext::new("TST3", &format!("CT{}", thread_id), "Test App 3", &format!("Thread {}", thread_id))
+                    .expect("Failed to register context");
                 for i in 0..5 {
                     local_ctx.log(&format!("Thread {} msg {}", thread_id, i)).unwrap();
                 }